@@ -1,16 +1,19 @@
-use std::collections::BTreeSet;
 use std::fs;
+use std::collections::BTreeSet;
 use std::process;
 use futures::future;
 use std::io::{BufRead, Write};
 use std::io::BufReader;
 use std::fs::File;
-use std::path::PathBuf;
-use crate::schema::{Enum, GqlDocument, GqlType, Object};
+use std::path::{Path, PathBuf};
+use crate::schema::{Field, GqlDocument, GqlType, Object, RootTypes};
 use crate::code_writer::CodeFile;
 use crate::code_writer::CodeFileOptions;
 
 const EMBEDDED_HASH_PREFIX: &str = "// hash:";
+const SDL_EMBEDDED_HASH_PREFIX: &str = "# hash:";
+const TYPENAME_FIELD: &str = "__typename";
+const FRAGMENTS_FIELD: &str = "__on";
 
 fn io_error_abort(context: &str, error: std::io::Error) -> ! {
     eprintln!("{}", context);
@@ -22,7 +25,8 @@ pub async fn write_files(
     document: GqlDocument,
     output_directory: PathBuf,
     options: CodeFileOptions,
-    runtime: &str
+    runtime: &str,
+    snapshot: Option<SchemaSnapshot>
 ) {
     if !output_directory.exists() {
         match fs::create_dir(&output_directory) {
@@ -45,27 +49,85 @@ pub async fn write_files(
     };
 
     let write_schema_task = async {
-        let content = write_schema_ts(
-            &document.inputs,
-            &document.outputs,
-            &document.scalars,
-            &document.enums,
-            &options,
-            runtime
-        );
+        let content = write_schema_ts(&document, &options, runtime);
         let path = &output_directory.join("schema.ts");
-        let result = overwrite_on_diff(path, &content, &options);
+        let result = overwrite_on_diff(path, &content, Some(EMBEDDED_HASH_PREFIX), &options);
         result.log("schema.ts");
     };
 
     let write_codec_task = async {
-        let content = write_codec_ts(&document.inputs, &document.outputs, &options, runtime);
+        let content = write_codec_ts(&document, &options, runtime);
         let path = &output_directory.join("codec.ts");
-        let result = overwrite_on_diff(path, &content, &options);
+        let result = overwrite_on_diff(path, &content, Some(EMBEDDED_HASH_PREFIX), &options);
         result.log("codec.ts");
     };
 
-    future::join3(create_index_task, write_schema_task, write_codec_task).await;
+    let write_snapshot_task = async {
+        if let Some(snapshot) = &snapshot {
+            let path = &output_directory.join(snapshot.file_name());
+            let result = overwrite_on_diff(path, snapshot.content(), snapshot.hash_prefix(), &options);
+            result.log(snapshot.file_name());
+        }
+    };
+
+    future::join4(create_index_task, write_schema_task, write_codec_task, write_snapshot_task).await;
+}
+
+/// Generates the files in memory and compares them with the ones in the output
+/// directory the same way as `overwrite_on_diff`, without writing anything.
+/// index.ts is left out as it is never overwritten. Returns true when all are up to date.
+pub fn check_files(
+    document: &GqlDocument,
+    output_directory: &Path,
+    options: &CodeFileOptions,
+    runtime: &str,
+    snapshot: &Option<SchemaSnapshot>
+) -> bool {
+    let mut files: Vec<(&str, String, Option<&str>)> = vec![
+        ("schema.ts", write_schema_ts(document, options, runtime), Some(EMBEDDED_HASH_PREFIX)),
+        ("codec.ts", write_codec_ts(document, options, runtime), Some(EMBEDDED_HASH_PREFIX))
+    ];
+    if let Some(snapshot) = snapshot {
+        files.push((snapshot.file_name(), snapshot.content().to_string(), snapshot.hash_prefix()));
+    }
+    let mut up_to_date = true;
+    for (file_name, content, hash_prefix) in files {
+        let path = &output_directory.join(file_name);
+        let result = compare_on_diff(path, &content, hash_prefix);
+        result.log(file_name);
+        up_to_date &= matches!(result, FileCheckResult::UpToDate);
+    }
+    up_to_date
+}
+
+/// Schema written next to the generated code so schema changes show up in
+/// version control, either printed as SDL or the introspection response.
+pub enum SchemaSnapshot {
+    Sdl(String),
+    Introspection(String)
+}
+
+impl SchemaSnapshot {
+    fn file_name(&self) -> &'static str {
+        match self {
+            SchemaSnapshot::Sdl(_) => "schema.graphql",
+            SchemaSnapshot::Introspection(_) => "schema.json"
+        }
+    }
+
+    fn content(&self) -> &str {
+        match self {
+            SchemaSnapshot::Sdl(content) | SchemaSnapshot::Introspection(content) => content
+        }
+    }
+
+    /// JSON has no comments, so the whole file is hashed instead.
+    fn hash_prefix(&self) -> Option<&'static str> {
+        match self {
+            SchemaSnapshot::Sdl(_) => Some(SDL_EMBEDDED_HASH_PREFIX),
+            SchemaSnapshot::Introspection(_) => None
+        }
+    }
 }
 
 fn write_index_ts(
@@ -93,11 +155,13 @@ fn write_index_ts(
     }
 }
 
-fn overwrite_on_diff(file_path: &PathBuf, new_content: &str, options: &CodeFileOptions) -> FileWriteResult {
+/// Writes the file unless its content is unchanged, the hash is embedded on the
+/// first line after `hash_prefix`, or compared against the whole file without one.
+fn overwrite_on_diff(file_path: &PathBuf, new_content: &str, hash_prefix: Option<&str>, options: &CodeFileOptions) -> FileWriteResult {
     let new_content_hash = crc32fast::hash(new_content.as_bytes());
 
     if file_path.exists() {
-        let skip = if let Some(hash) = read_embedded_hash(file_path) {
+        let skip = if let Some(hash) = read_existing_hash(file_path, hash_prefix) {
             hash == new_content_hash
         } else {
             false
@@ -113,7 +177,7 @@ fn overwrite_on_diff(file_path: &PathBuf, new_content: &str, options: &CodeFileO
                     error
                 )
             };
-            match write_all_with_hash(&mut file, new_content, new_content_hash, options) {
+            match write_all_with_hash(&mut file, new_content, new_content_hash, hash_prefix, options) {
                 Ok(()) => (),
                 Err(error) => io_error_abort(
                     &format!("Unable to write to file {}", file_path.display()),
@@ -130,7 +194,7 @@ fn overwrite_on_diff(file_path: &PathBuf, new_content: &str, options: &CodeFileO
                 error
             )
         };
-        match write_all_with_hash(&mut file, new_content, new_content_hash, options) {
+        match write_all_with_hash(&mut file, new_content, new_content_hash, hash_prefix, options) {
             Ok(()) => (),
             Err(error) => io_error_abort(
                 &format!("Unable to write to file {}", file_path.display()),
@@ -141,7 +205,35 @@ fn overwrite_on_diff(file_path: &PathBuf, new_content: &str, options: &CodeFileO
     }
 }
 
-fn read_embedded_hash(path: &PathBuf) -> Option<u32> {
+fn compare_on_diff(file_path: &PathBuf, new_content: &str, hash_prefix: Option<&str>) -> FileCheckResult {
+    let new_content_hash = crc32fast::hash(new_content.as_bytes());
+    if !file_path.exists() {
+        FileCheckResult::Missing
+    } else if read_existing_hash(file_path, hash_prefix) == Some(new_content_hash) {
+        FileCheckResult::UpToDate
+    } else {
+        FileCheckResult::Stale
+    }
+}
+
+fn read_existing_hash(path: &PathBuf, hash_prefix: Option<&str>) -> Option<u32> {
+    match hash_prefix {
+        Some(hash_prefix) => read_embedded_hash(path, hash_prefix),
+        None => Some(read_content_hash(path))
+    }
+}
+
+fn read_content_hash(path: &PathBuf) -> u32 {
+    match fs::read(path) {
+        Ok(content) => crc32fast::hash(&content),
+        Err(error) => io_error_abort(
+            &format!("Failed while trying to read {} in order to hash it", path.display()),
+            error
+        )
+    }
+}
+
+fn read_embedded_hash(path: &PathBuf, hash_prefix: &str) -> Option<u32> {
     let file = match File::open(path) {
         Ok(file) => file,
         Err(error) => io_error_abort(
@@ -159,15 +251,12 @@ fn read_embedded_hash(path: &PathBuf) -> Option<u32> {
         )
     }
 
-    if hash_line.starts_with(EMBEDDED_HASH_PREFIX) {
-        let offset = EMBEDDED_HASH_PREFIX.chars().count();
+    if hash_line.starts_with(hash_prefix) {
+        let offset = hash_prefix.chars().count();
         match hash_line.get(offset..) {
             Some(hash_string) => {
                 let file_hash = hash_string.trim_end();
-                match file_hash.parse() {
-                    Ok(hash) => Some(hash),
-                    Err(_) => None
-                }
+                file_hash.parse().ok()
             }
             None => None
         }
@@ -176,9 +265,20 @@ fn read_embedded_hash(path: &PathBuf) -> Option<u32> {
     }
 }
 
-fn write_all_with_hash(file: &mut File, new_content: &str, hash: u32, options: &CodeFileOptions) -> Result<(), std::io::Error> {
-    let content_with_hash = format!("{}{}{}{}", EMBEDDED_HASH_PREFIX, hash, options.line_break, new_content);
-    file.write_all(content_with_hash.as_bytes())
+fn write_all_with_hash(
+    file: &mut File,
+    new_content: &str,
+    hash: u32,
+    hash_prefix: Option<&str>,
+    options: &CodeFileOptions
+) -> Result<(), std::io::Error> {
+    match hash_prefix {
+        Some(hash_prefix) => {
+            let content_with_hash = format!("{}{}{}{}", hash_prefix, hash, options.line_break, new_content);
+            file.write_all(content_with_hash.as_bytes())
+        }
+        None => file.write_all(new_content.as_bytes())
+    }
 }
 
 enum FileWriteResult {
@@ -197,20 +297,34 @@ impl FileWriteResult {
     }
 }
 
+enum FileCheckResult {
+    UpToDate,
+    Stale,
+    Missing
+}
+
+impl FileCheckResult {
+    fn log(&self, file_name: &str) {
+        match &self {
+            FileCheckResult::UpToDate => println!("{} - up to date", file_name),
+            FileCheckResult::Stale => println!("{} - would be overwritten", file_name),
+            FileCheckResult::Missing => println!("{} - would be created", file_name)
+        }
+    }
+}
+
 fn write_schema_ts(
-    inputs: &Vec<Object>,
-    outputs: &Vec<Object>,
-    scalars: &BTreeSet<String>,
-    enums: &Vec<Enum>,
+    document: &GqlDocument,
     options: &CodeFileOptions,
     runtime: &str
 ) -> String {
+    let GqlDocument { inputs, outputs, interfaces, unions, enums, scalars, root_types } = document;
     let mut file = CodeFile::new(options);
-    file.line(&format!("import {{ Scalar, QFun, QList, QNull, QObject, QScalar, QEnum }} from \"{}\"", runtime));
+    file.line(&format!("import {{ Scalar, QFun, QList, QNull, QObject, QScalar, QEnum, QFragments }} from \"{}\"", runtime));
     file.blank_line();
 
     file.begin_indent("export interface Scalars {");
-    for scalar in scalars {
+    for scalar in scalars.keys() {
         file.line(&format!("{}: {}", scalar, "Scalar<unknown, unknown>"));
     }
     file.end_indent("}");
@@ -220,22 +334,46 @@ fn write_schema_ts(
     file.line("return scalars");
     file.end_indent("}");
 
+    write_root_types(&mut file, root_types);
+
     for enum_def in enums {
         file.blank_line();
+        file.doc_comment(&description_doc(&enum_def.description));
         file.begin_indent(&format!("export enum {} {{", enum_def.name));
         for member in &enum_def.values {
-            file.line(&format!("{} = \"{}\",", member, member));
+            file.doc_comment(&documentation(&member.description, &member.deprecated));
+            file.line(&format!("{} = \"{}\",", member.name, member.name));
         }
         file.end_indent("}");
     }
 
-    if !outputs.is_empty() {
+    if !outputs.is_empty() || !interfaces.is_empty() || !unions.is_empty() {
         file.begin_indent("export type ObjectSchema = {");
         for output in outputs {
+            file.doc_comment(&description_doc(&output.description));
             file.begin_indent(&format!("{}: {{", output.name));
             for field in &output.fields {
+                file.doc_comment(&field_doc(field));
+                file.line(&format!("{}: {}", field.name, gql_type_to_code(&field.field_type)));
+            }
+            file.end_indent("}");
+        }
+        for interface in interfaces {
+            file.doc_comment(&description_doc(&interface.description));
+            file.begin_indent(&format!("{}: {{", interface.name));
+            for field in &interface.fields {
+                file.doc_comment(&field_doc(field));
                 file.line(&format!("{}: {}", field.name, gql_type_to_code(&field.field_type)));
             }
+            file.line(&format!("{}: QScalar<\"String\">", TYPENAME_FIELD));
+            file.line(&format!("{}: {}", FRAGMENTS_FIELD, fragments_to_code(&interface.implementors)));
+            file.end_indent("}");
+        }
+        for union in unions {
+            file.doc_comment(&description_doc(&union.description));
+            file.begin_indent(&format!("{}: {{", union.name));
+            file.line(&format!("{}: QScalar<\"String\">", TYPENAME_FIELD));
+            file.line(&format!("{}: {}", FRAGMENTS_FIELD, fragments_to_code(&union.members)));
             file.end_indent("}");
         }
         file.end_indent("}");
@@ -245,9 +383,15 @@ fn write_schema_ts(
     if !inputs.is_empty() {
         file.begin_indent("export type InputObjectSchema = {");
         for input in inputs {
+            file.doc_comment(&description_doc(&input.description));
+            if input.one_of && !input.fields.is_empty() {
+                write_one_of_input(&mut file, input);
+                continue;
+            }
             file.begin_indent(&format!("{}: {{", input.name));
             for field in &input.fields {
-                let optional_marker = if let GqlType::Nullable(_) = field.field_type {
+                file.doc_comment(&field_doc(field));
+                let optional_marker = if is_optional(&field.field_type, &field.default_value) {
                     "?"
                 } else {
                     ""
@@ -261,6 +405,76 @@ fn write_schema_ts(
     file.build_string()
 }
 
+/// A oneOf input becomes a union with one member per field, where the selected
+/// field is required and every other field is `never`.
+fn write_one_of_input(file: &mut CodeFile, input: &Object) {
+    file.begin_indent(&format!("{}: {{", input.name));
+    for (index, selected) in input.fields.iter().enumerate() {
+        if index > 0 {
+            file.continue_indent("} | {");
+        }
+        for field in &input.fields {
+            if field.name == selected.name {
+                let field_type = match &field.field_type {
+                    GqlType::Nullable(inner) => inner,
+                    field_type => field_type
+                };
+                file.doc_comment(&field_doc(field));
+                file.line(&format!("{}: {}", field.name, gql_type_to_code(field_type)));
+            } else {
+                file.line(&format!("{}?: never", field.name));
+            }
+        }
+    }
+    file.end_indent("}");
+}
+
+fn description_doc(description: &Option<String>) -> Vec<String> {
+    description.iter().cloned().collect()
+}
+
+fn documentation(description: &Option<String>, deprecated: &Option<String>) -> Vec<String> {
+    let mut doc = description_doc(description);
+    if let Some(reason) = deprecated {
+        doc.push(format!("@deprecated {}", reason));
+    }
+    doc
+}
+
+fn field_doc(field: &Field) -> Vec<String> {
+    let mut doc = documentation(&field.description, &field.deprecated);
+    if let GqlType::Function { inputs, .. } = &field.field_type {
+        for input in inputs {
+            if let Some(description) = &input.description {
+                doc.push(format!("@param {} {}", input.name, description));
+            }
+        }
+    }
+    doc
+}
+
+fn write_root_types(file: &mut CodeFile, root_types: &RootTypes) {
+    let operations = [
+        ("query", &root_types.query),
+        ("mutation", &root_types.mutation),
+        ("subscription", &root_types.subscription)
+    ];
+    file.blank_line();
+    file.begin_indent("export const rootTypes = {");
+    for (operation, type_name) in operations {
+        if let Some(type_name) = type_name {
+            file.line(&format!("{}: \"{}\",", operation, type_name));
+        }
+    }
+    file.end_indent("} as const");
+    file.blank_line();
+    file.line("export type RootTypes = typeof rootTypes");
+}
+
+fn is_optional(gql_type: &GqlType, default_value: &Option<String>) -> bool {
+    matches!(gql_type, GqlType::Nullable(_)) || default_value.is_some()
+}
+
 fn gql_type_to_code(gql_type: &GqlType) -> String {
     match gql_type {
         GqlType::List(inner) => format!("QList<{}>", gql_type_to_code(inner)),
@@ -270,9 +484,12 @@ fn gql_type_to_code(gql_type: &GqlType) -> String {
         GqlType::Object(name) => format!("QObject<\"{}\">", name),
         GqlType::Function { inputs, output } => {
             let input_as_code: Vec<String> = inputs.iter().map(|arg| {
-                let name = arg.name.clone();
+                let name = match &arg.deprecated {
+                    Some(reason) => format!("/** @deprecated {} */ {}", reason.replace("*/", "*\\/").replace('\n', " "), arg.name),
+                    None => arg.name.clone()
+                };
                 let gql_type = &arg.argument_type;
-                if let GqlType::Nullable(_) = gql_type {
+                if is_optional(gql_type, &arg.default_value) {
                     format!("{}?: {}", name, gql_type_to_code(gql_type))
                 } else {
                     format!("{}: {}", name, gql_type_to_code(gql_type))
@@ -283,15 +500,28 @@ fn gql_type_to_code(gql_type: &GqlType) -> String {
     }
 }
 
+fn fragments_to_code(members: &[String]) -> String {
+    let members_as_code: Vec<String> = members
+        .iter()
+        .map(|member| format!("{}: \"{}\"", member, member))
+        .collect();
+    format!("QFragments<{{ {} }}>", members_as_code.join(", "))
+}
+
 fn write_codec_ts(
-    inputs: &Vec<Object>,
-    outputs: &Vec<Object>,
+    document: &GqlDocument,
     options: &CodeFileOptions,
     runtime: &str
 ) -> String {
+    let GqlDocument { inputs, outputs, interfaces, unions, .. } = document;
+    let one_of_inputs: BTreeSet<&str> = inputs
+        .iter()
+        .filter(|input| input.one_of)
+        .map(|input| input.name.as_str())
+        .collect();
     let mut file = CodeFile::new(options);
     file.line("import { Scalars } from \"./schema\"");
-    file.line(&format!("import {{ Codec, Encoder, decodeNull, decodeList, decodeObject, encodeNull, encodeList, encodeObject }} from \"{}\"", runtime));
+    file.line(&format!("import {{ Codec, Encoder, decodeNull, decodeList, decodeObject, encodeNull, encodeList, encodeObject, encodeOneOf }} from \"{}\"", runtime));
     file.blank_line();
 
     file.begin_indent("export class SchemaCodec {");
@@ -303,7 +533,7 @@ fn write_codec_ts(
     for object in inputs {
         file.begin_indent(&format!("public {}: Encoder = {{", object.name));
         for field in &object.fields {
-            file.line(&format!("{}: (value) => {},", field.name, encode_to_code(&field.field_type)));
+            file.line(&format!("{}: (value) => {},", field.name, encode_to_code(&field.field_type, &one_of_inputs)));
         }
         file.end_indent("}");
     }
@@ -311,27 +541,23 @@ fn write_codec_ts(
     for object in outputs {
         file.begin_indent(&format!("public {}: Codec = {{", object.name));
         for field in &object.fields {
-            file.begin_indent(&format!("{}: {{", field.name));
+            write_codec_field(&mut file, &field.name, &field.field_type, &one_of_inputs);
+        }
+        file.end_indent("}");
+    }
 
-            match resolve_encoding_target(&field.field_type) {
-                EncodingTarget::SingleField => (),
-                EncodingTarget::Object(name) => {
-                    file.line(&format!("codec: () => this.{},", name));
-                }
-            }
-            file.line(&format!("decode: (value) => {},", decode_to_code(&field.field_type)));
-            if let GqlType::Function { inputs, .. } = &field.field_type {
-                file.begin_indent("args: {");
-                for input in inputs {
-                    file.begin_indent(&format!("{}: {{", input.name));
-                    file.line(&format!("type: \"{}\",", input.type_name));
-                    file.line(&format!("encode: (value) => {},", encode_to_code(&input.argument_type)));
-                    file.end_indent("},");
-                }
-                file.end_indent("}");
-            }
-            file.end_indent("},");
+    for interface in interfaces {
+        file.begin_indent(&format!("public {}: Codec = {{", interface.name));
+        for field in &interface.fields {
+            write_codec_field(&mut file, &field.name, &field.field_type, &one_of_inputs);
         }
+        write_fragments_codec(&mut file, &interface.implementors, &one_of_inputs);
+        file.end_indent("}");
+    }
+
+    for union in unions {
+        file.begin_indent(&format!("public {}: Codec = {{", union.name));
+        write_fragments_codec(&mut file, &union.members, &one_of_inputs);
         file.end_indent("}");
     }
 
@@ -339,6 +565,41 @@ fn write_codec_ts(
     file.build_string()
 }
 
+fn write_codec_field(file: &mut CodeFile, name: &str, field_type: &GqlType, one_of_inputs: &BTreeSet<&str>) {
+    file.begin_indent(&format!("{}: {{", name));
+
+    match resolve_encoding_target(field_type) {
+        EncodingTarget::SingleField => (),
+        EncodingTarget::Object(name) => {
+            file.line(&format!("codec: () => this.{},", name));
+        }
+    }
+    file.line(&format!("decode: (value) => {},", decode_to_code(field_type)));
+    if let GqlType::Function { inputs, .. } = field_type {
+        file.begin_indent("args: {");
+        for input in inputs {
+            file.begin_indent(&format!("{}: {{", input.name));
+            file.line(&format!("type: \"{}\",", input.type_name));
+            file.line(&format!("encode: (value) => {},", encode_to_code(&input.argument_type, one_of_inputs)));
+            file.end_indent("},");
+        }
+        file.end_indent("}");
+    }
+    file.end_indent("},");
+}
+
+fn write_fragments_codec(file: &mut CodeFile, members: &[String], one_of_inputs: &BTreeSet<&str>) {
+    write_codec_field(file, TYPENAME_FIELD, &GqlType::Scalar("String".to_string()), one_of_inputs);
+    file.begin_indent(&format!("{}: {{", FRAGMENTS_FIELD));
+    file.line("decode: (value) => value,");
+    file.begin_indent("fragments: {");
+    for member in members {
+        file.line(&format!("{}: () => this.{},", member, member));
+    }
+    file.end_indent("},");
+    file.end_indent("},");
+}
+
 fn resolve_encoding_target(gql_type: &GqlType) -> EncodingTarget {
     match gql_type {
         GqlType::Scalar(_) => EncodingTarget::SingleField,
@@ -366,13 +627,109 @@ fn decode_to_code(gql_type: &GqlType) -> String {
     }
 }
 
-fn encode_to_code(gql_type: &GqlType) -> String {
+fn encode_to_code(gql_type: &GqlType, one_of_inputs: &BTreeSet<&str>) -> String {
     match gql_type {
-        GqlType::Nullable(inner) => format!("encodeNull(value, value => {})", encode_to_code(inner)),
-        GqlType::List(inner) => format!("encodeList(value, value => {})", encode_to_code(inner)),
+        GqlType::Nullable(inner) => format!("encodeNull(value, value => {})", encode_to_code(inner, one_of_inputs)),
+        GqlType::List(inner) => format!("encodeList(value, value => {})", encode_to_code(inner, one_of_inputs)),
         GqlType::Enum(_) => "value".to_string(),
         GqlType::Scalar(name) => format!("this.scalars.{}.encode(value)", name),
+        GqlType::Object(name) if one_of_inputs.contains(name.as_str()) => format!("encodeOneOf(value, this.{})", name),
         GqlType::Object(name) => format!("encodeObject(value, this.{})", name),
-        GqlType::Function { .. } => panic!("Unable to encode argument as function inside function"),
+        GqlType::Function { .. } => unreachable!("Arguments cannot be encoded, input types have no field arguments")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SchemaSource;
+    use crate::schema_sdl;
+
+    fn document(sdl: &str) -> GqlDocument {
+        let sources = [SchemaSource { name: None, content: sdl.to_string() }];
+        schema_sdl::from_sdl_sources(&sources).ok().unwrap().0
+    }
+
+    fn options() -> CodeFileOptions {
+        CodeFileOptions { line_break: "\n".to_string(), indent: "    ".to_string() }
+    }
+
+    fn generate(sdl: &str) -> (String, String) {
+        let document = document(sdl);
+        let schema = write_schema_ts(&document, &options(), "graphql-freeze");
+        let codec = write_codec_ts(&document, &options(), "graphql-freeze");
+        (schema, codec)
+    }
+
+    #[test]
+    fn generates_interface_with_implementor_fragments() {
+        let (schema, codec) = generate(
+            "type Query { node: Node }\ninterface Node { id: ID! }\ntype User implements Node { id: ID! name: String }"
+        );
+        assert!(schema.contains(
+            "    Node: {\n        id: QScalar<\"ID\">\n        __typename: QScalar<\"String\">\n        __on: QFragments<{ User: \"User\" }>\n    }"
+        ));
+        assert!(schema.contains("node: QNull<QObject<\"Node\">>"));
+        assert!(codec.contains("    public Node: Codec = {"));
+        assert!(codec.contains("            fragments: {\n                User: () => this.User,\n            },"));
+    }
+
+    #[test]
+    fn generates_union_with_member_fragments() {
+        let (schema, codec) = generate(
+            "type Query { search: [Result!]! }\nunion Result = User | Post\ntype User { name: String }\ntype Post { title: String }"
+        );
+        assert!(schema.contains(
+            "    Result: {\n        __typename: QScalar<\"String\">\n        __on: QFragments<{ User: \"User\", Post: \"Post\" }>\n    }"
+        ));
+        assert!(schema.contains("search: QList<QObject<\"Result\">>"));
+        assert!(codec.contains("            fragments: {\n                User: () => this.User,\n                Post: () => this.Post,\n            },"));
+    }
+
+    #[test]
+    fn makes_arguments_and_input_fields_with_default_values_optional() {
+        let (schema, _) = generate(
+            "type Query { users(first: Int! = 10, after: String!): [String!]! }\ninput Filter { limit: Int! = 20 name: String! }"
+        );
+        assert!(schema.contains("users: QFun<{ first?: QScalar<\"Int\">, after: QScalar<\"String\"> }, QList<QScalar<\"String\">>>"));
+        assert!(schema.contains("        limit?: QScalar<\"Int\">\n        name: QScalar<\"String\">\n"));
+    }
+
+    #[test]
+    fn compares_files_by_embedded_or_content_hash() {
+        let root = std::env::temp_dir().join(format!("code-generator-compare-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        for (file_name, hash_prefix) in [("schema.ts", Some(EMBEDDED_HASH_PREFIX)), ("schema.json", None)] {
+            let path = &root.join(file_name);
+            assert!(matches!(compare_on_diff(path, "{}", hash_prefix), FileCheckResult::Missing));
+            overwrite_on_diff(path, "{}", hash_prefix, &options());
+            assert!(matches!(compare_on_diff(path, "{}", hash_prefix), FileCheckResult::UpToDate));
+            assert!(matches!(compare_on_diff(path, "{ }", hash_prefix), FileCheckResult::Stale));
+        }
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn checks_generated_files_without_writing() {
+        let root = std::env::temp_dir().join(format!("code-generator-check-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let current = document("type Query { id: ID }");
+        let snapshot = Some(SchemaSnapshot::Sdl("type Query {\n    id: ID\n}\n".to_string()));
+        assert!(!check_files(&current, &root, &options(), "graphql-freeze", &snapshot));
+        assert!(!root.join("schema.ts").exists());
+
+        let files = [
+            ("schema.ts", write_schema_ts(&current, &options(), "graphql-freeze"), Some(EMBEDDED_HASH_PREFIX)),
+            ("codec.ts", write_codec_ts(&current, &options(), "graphql-freeze"), Some(EMBEDDED_HASH_PREFIX)),
+            ("schema.graphql", snapshot.as_ref().unwrap().content().to_string(), Some(SDL_EMBEDDED_HASH_PREFIX))
+        ];
+        for (file_name, content, hash_prefix) in &files {
+            overwrite_on_diff(&root.join(file_name), content, *hash_prefix, &options());
+        }
+        assert!(check_files(&current, &root, &options(), "graphql-freeze", &snapshot));
+
+        let changed = document("type Query { id: ID, name: String }");
+        assert!(!check_files(&changed, &root, &options(), "graphql-freeze", &snapshot));
+        fs::remove_dir_all(root).unwrap();
     }
 }
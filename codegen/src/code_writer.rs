@@ -39,6 +39,29 @@ impl CodeFile {
         self.content.push_str(&self.line_break);
     }
 
+    pub fn doc_comment(&mut self, lines: &[String]) {
+        let lines: Vec<String> = lines
+            .iter()
+            .flat_map(|line| line.lines())
+            .map(|line| line.trim_end().replace("*/", "*\\/"))
+            .collect();
+        match lines.as_slice() {
+            [] => (),
+            [line] => self.line(&format!("/** {} */", line)),
+            lines => {
+                self.line("/**");
+                for line in lines {
+                    if line.is_empty() {
+                        self.line(" *");
+                    } else {
+                        self.line(&format!(" * {}", line));
+                    }
+                }
+                self.line(" */");
+            }
+        }
+    }
+
     pub fn blank_line(&mut self) {
         self.content.push_str(&self.line_break);
     }
@@ -53,6 +76,12 @@ impl CodeFile {
         self.line(code);
     }
 
+    pub fn continue_indent(&mut self, code: &str) {
+        self.deindent();
+        self.line(code);
+        self.indent();
+    }
+
     pub fn build_string(self) -> String {
         self.content
     }
@@ -0,0 +1,63 @@
+use std::fmt;
+use std::io;
+use std::process::{ExitStatus, Stdio};
+use tokio::process::Command;
+
+pub struct CommandOptions {
+    pub program: String,
+    pub args: Vec<String>
+}
+
+impl CommandOptions {
+    pub fn display_name(&self) -> String {
+        let mut parts = vec![self.program.clone()];
+        parts.extend(self.args.iter().cloned());
+        parts.join(" ")
+    }
+}
+
+pub enum CommandError {
+    Spawn(io::Error),
+    Failed {
+        status: ExitStatus,
+        stderr: String
+    },
+    InvalidOutput
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Spawn(error) => write!(f, "Unable to start command: {}", error),
+            CommandError::Failed { status, stderr } => {
+                match status.code() {
+                    Some(code) => write!(f, "Command exited with code {}", code)?,
+                    None => write!(f, "Command was terminated by a signal")?
+                }
+                let stderr = stderr.trim();
+                if !stderr.is_empty() {
+                    write!(f, "\n{}", stderr)?;
+                }
+                Ok(())
+            }
+            CommandError::InvalidOutput => write!(f, "Command output is not valid UTF-8")
+        }
+    }
+}
+
+/// Runs the program without a shell and returns what it wrote to stdout.
+pub async fn read_output(options: &CommandOptions) -> Result<String, CommandError> {
+    let output = Command::new(&options.program)
+        .args(&options.args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(CommandError::Spawn)?;
+    if !output.status.success() {
+        return Err(CommandError::Failed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).to_string()
+        });
+    }
+    String::from_utf8(output.stdout).map_err(|_| CommandError::InvalidOutput)
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use graphql_parser::Pos;
+use crate::schema::SchemaSource;
+
+pub const SYNTAX_ERROR: &str = "E0001";
+pub const UNKNOWN_TYPE: &str = "E0002";
+pub const UNDEFINED_EXTENSION: &str = "E0003";
+pub const INVALID_INPUT_TYPE: &str = "E0004";
+pub const INVALID_INTROSPECTION: &str = "E0005";
+pub const DUPLICATE_DEFINITION: &str = "E0006";
+pub const INVALID_TYPE_REFERENCE: &str = "E0007";
+pub const INVALID_ONE_OF_FIELD: &str = "E0008";
+pub const SKIPPED_DIRECTIVE_DEFINITION: &str = "W0001";
+pub const SKIPPED_DIRECTIVE: &str = "W0002";
+pub const PARTIAL_INTROSPECTION: &str = "W0003";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning")
+        }
+    }
+}
+
+pub struct Location {
+    pub source_name: Option<String>,
+    pub position: Option<Pos>
+}
+
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub location: Option<Location>
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: String) -> Diagnostic {
+        Diagnostic { severity: Severity::Error, code, message, location: None }
+    }
+
+    pub fn warning(code: &'static str, message: String) -> Diagnostic {
+        Diagnostic { severity: Severity::Warning, code, message, location: None }
+    }
+
+    pub fn at(mut self, source_name: &Option<String>, position: Option<Pos>) -> Diagnostic {
+        self.location = Some(Location { source_name: source_name.clone(), position });
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Prints diagnostics to stderr in the style of rustc, with the offending
+/// source line when the location points into one of `sources`.
+pub fn print(diagnostics: &[Diagnostic], sources: &[SchemaSource]) {
+    for diagnostic in diagnostics {
+        eprintln!("{}", render(diagnostic, sources));
+    }
+    let error_count = diagnostics.iter().filter(|diagnostic| diagnostic.is_error()).count();
+    let warning_count = diagnostics.len() - error_count;
+    if error_count > 0 {
+        eprintln!("error: found {} error(s) and {} warning(s)", error_count, warning_count);
+    } else if warning_count > 0 {
+        eprintln!("warning: found {} warning(s)", warning_count);
+    }
+}
+
+fn render(diagnostic: &Diagnostic, sources: &[SchemaSource]) -> String {
+    let mut text = format!("{}[{}]: {}", diagnostic.severity, diagnostic.code, diagnostic.message);
+    let location = match &diagnostic.location {
+        Some(location) => location,
+        None => return text
+    };
+    let source_label = location.source_name.as_deref().unwrap_or("<stdin>");
+    let position = match location.position {
+        Some(position) => position,
+        None => {
+            text.push_str(&format!("\n --> {}", source_label));
+            return text;
+        }
+    };
+    text.push_str(&format!("\n --> {}:{}:{}", source_label, position.line, position.column));
+    let source_line = sources
+        .iter()
+        .find(|source| source.name == location.source_name)
+        .and_then(|source| source.content.lines().nth(position.line.saturating_sub(1)));
+    if let Some(source_line) = source_line {
+        let gutter = " ".repeat(position.line.to_string().len());
+        let marker = " ".repeat(position.column.saturating_sub(1));
+        text.push_str(&format!("\n{} |", gutter));
+        text.push_str(&format!("\n{} | {}", position.line, source_line));
+        text.push_str(&format!("\n{} | {}^", gutter, marker));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources() -> Vec<SchemaSource> {
+        vec![SchemaSource { name: Some("schema.graphql".to_string()), content: "type Query {\n    user: Usr\n}".to_string() }]
+    }
+
+    #[test]
+    fn renders_source_line_with_marker() {
+        let diagnostic = Diagnostic::error(UNKNOWN_TYPE, "Unknown type `Usr` in Query.user".to_string())
+            .at(&Some("schema.graphql".to_string()), Some(Pos { line: 2, column: 11 }));
+        assert_eq!(
+            render(&diagnostic, &sources()),
+            "error[E0002]: Unknown type `Usr` in Query.user\n --> schema.graphql:2:11\n  |\n2 |     user: Usr\n  |           ^"
+        );
+    }
+
+    #[test]
+    fn renders_location_without_source_line() {
+        let without_position = Diagnostic::error(SYNTAX_ERROR, "Unexpected end of input".to_string()).at(&None, None);
+        assert_eq!(render(&without_position, &sources()), "error[E0001]: Unexpected end of input\n --> <stdin>");
+        let unknown_source = Diagnostic::error(SYNTAX_ERROR, "Unexpected `}`".to_string())
+            .at(&Some("other.graphql".to_string()), Some(Pos { line: 4, column: 1 }));
+        assert_eq!(render(&unknown_source, &sources()), "error[E0001]: Unexpected `}`\n --> other.graphql:4:1");
+        let without_location = Diagnostic::error(INVALID_INTROSPECTION, "Missing __schema".to_string());
+        assert_eq!(render(&without_location, &sources()), "error[E0005]: Missing __schema");
+    }
+}
@@ -0,0 +1,448 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::schema_introspection;
+
+/// Wrapper depth of the `TypeRef` fragment in the first introspection request.
+const TYPE_REF_DEPTH: usize = 9;
+/// Deepest `TypeRef` fragment requested when resolving truncated type references.
+const MAX_TYPE_REF_DEPTH: usize = 72;
+
+pub const DEFAULT_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 60;
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+/// Longest part of a response body included in error messages.
+const MAX_ERROR_BODY_LENGTH: usize = 1000;
+
+const ONE_OF_PLACEHOLDER: &str = "__ONE_OF__";
+const ONE_OF_FRAGMENT: &str = "fragment OneOf on __Type { isOneOf }\n";
+const SPECIFIED_BY_URL_PLACEHOLDER: &str = "__SPECIFIED_BY_URL__";
+const SPECIFIED_BY_URL_FRAGMENT: &str = "fragment SpecifiedByUrl on __Type { specifiedByURL }\n";
+const INPUT_VALUE_ARGUMENTS_PLACEHOLDER: &str = "__INPUT_VALUE_ARGUMENTS__";
+const INPUT_VALUE_DEPRECATION_PLACEHOLDER: &str = "__INPUT_VALUE_DEPRECATION__";
+const INPUT_VALUE_DEPRECATION_FRAGMENT: &str = "fragment InputValueDeprecation on __InputValue { isDeprecated deprecationReason }\n";
+
+pub struct EndpointOptions {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub connect_timeout: Duration,
+    pub timeout: Duration,
+    pub retries: u32,
+    pub proxy: Option<String>,
+    pub tls: TlsOptions
+}
+
+#[derive(Default)]
+pub struct TlsOptions {
+    /// PEM files with root certificates trusted in addition to the built-in ones.
+    pub root_certificates: Vec<PathBuf>,
+    pub client_certificate: Option<ClientCertificate>,
+    pub accept_invalid_certificates: bool
+}
+
+pub struct ClientCertificate {
+    pub certificate: PathBuf,
+    pub key: PathBuf
+}
+
+pub enum EndpointError {
+    Http(reqwest::Error),
+    Setup(String),
+    Status {
+        url: String,
+        status: StatusCode,
+        body: String
+    },
+    Json(serde_json::Error)
+}
+
+impl EndpointError {
+    /// Connection problems, timeouts and server errors may pass once the
+    /// server is up, anything else fails the same way on every attempt.
+    fn is_retryable(&self) -> bool {
+        match self {
+            EndpointError::Http(error) => error.is_connect() || error.is_timeout(),
+            EndpointError::Status { status, .. } => status.is_server_error(),
+            EndpointError::Setup(_) | EndpointError::Json(_) => false
+        }
+    }
+}
+
+impl fmt::Display for EndpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndpointError::Http(error) => {
+                let mut message = format!("Networking error: {}", error);
+                let mut source = error.source();
+                while let Some(cause) = source {
+                    let cause_message = cause.to_string();
+                    if !message.contains(&cause_message) {
+                        message.push_str(&format!(": {}", cause_message));
+                    }
+                    source = cause.source();
+                }
+                write!(f, "{}", message)
+            }
+            EndpointError::Setup(message) => write!(f, "{}", message),
+            EndpointError::Status { url, status, body } => {
+                write!(f, "{} responded with HTTP status {}", url, status)?;
+                if !body.trim().is_empty() {
+                    write!(f, "\n{}", truncate(body.trim(), MAX_ERROR_BODY_LENGTH))?;
+                }
+                Ok(())
+            }
+            EndpointError::Json(error) => write!(f, "Endpoint returned invalid JSON: {}", error)
+        }
+    }
+}
+
+impl From<reqwest::Error> for EndpointError {
+    fn from(error: reqwest::Error) -> EndpointError {
+        EndpointError::Http(error)
+    }
+}
+
+impl From<serde_json::Error> for EndpointError {
+    fn from(error: serde_json::Error) -> EndpointError {
+        EndpointError::Json(error)
+    }
+}
+
+/// Sends the introspection query, then refetches every type whose field types
+/// are wrapped deeper than the `TypeRef` fragment reached, doubling the depth
+/// each round until all type references are complete.
+///
+/// Servers that reject optional parts of the query, such as `isOneOf`, are
+/// asked again without those parts.
+pub async fn read_introspection(options: &EndpointOptions) -> Result<String, EndpointError> {
+    let client = build_client(options)?;
+    let mut depth = TYPE_REF_DEPTH;
+    let mut features = IntrospectionFeatures::ALL;
+    let mut response_body = post_query(&client, options, &introspection_document(depth, features), "IntrospectionQuery", json!({})).await?;
+    loop {
+        let supported = features.without_rejected(&response_body);
+        if supported == features {
+            break;
+        }
+        features = supported;
+        response_body = post_query(&client, options, &introspection_document(depth, features), "IntrospectionQuery", json!({})).await?;
+    }
+    loop {
+        let truncated = match schema_introspection::truncated_type_names(&response_body) {
+            Ok(names) => names,
+            Err(_) => return Ok(response_body)
+        };
+        if truncated.is_empty() || depth >= MAX_TYPE_REF_DEPTH {
+            return Ok(response_body);
+        }
+        depth = (depth * 2).min(MAX_TYPE_REF_DEPTH);
+        let mut response: Value = serde_json::from_str(&response_body)?;
+        for name in truncated {
+            let document = introspection_document(depth, features);
+            let type_body = post_query(&client, options, &document, "IntrospectionType", json!({ "name": name })).await?;
+            let type_response: Value = serde_json::from_str(&type_body)?;
+            if let Some(full_type) = type_response.pointer("/data/__type") {
+                replace_type(&mut response, full_type.clone());
+            }
+        }
+        response_body = response.to_string();
+    }
+}
+
+/// Downloads schema SDL served as plain text, e.g. `GET /schema.graphql`.
+pub async fn read_sdl(options: &EndpointOptions) -> Result<String, EndpointError> {
+    let client = build_client(options)?;
+    send_with_retry(options, || client.get(&options.url)).await
+}
+
+fn build_client(options: &EndpointOptions) -> Result<reqwest::Client, EndpointError> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(options.connect_timeout)
+        .timeout(options.timeout);
+    if let Some(proxy) = &options.proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|error| EndpointError::Setup(format!("Invalid proxy {}: {}", proxy, error)))?;
+        builder = builder.proxy(proxy);
+    }
+    for path in &options.tls.root_certificates {
+        let certificates = reqwest::Certificate::from_pem_bundle(&read_pem(path)?)
+            .map_err(|error| EndpointError::Setup(format!("Invalid certificate in {}: {}", path.display(), error)))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if let Some(client_certificate) = &options.tls.client_certificate {
+        let mut pem = read_pem(&client_certificate.certificate)?;
+        pem.push(b'\n');
+        pem.extend(read_pem(&client_certificate.key)?);
+        let identity = reqwest::Identity::from_pem(&pem)
+            .map_err(|error| EndpointError::Setup(format!("Invalid client certificate or key: {}", error)))?;
+        builder = builder.identity(identity);
+    }
+    if options.tls.accept_invalid_certificates {
+        eprintln!("WARNING: Certificate validation is disabled for {}", options.url);
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder.build()?)
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>, EndpointError> {
+    fs::read(path).map_err(|error| EndpointError::Setup(format!("Unable to read {}: {}", path.display(), error)))
+}
+
+async fn send_with_retry(
+    options: &EndpointOptions,
+    request: impl Fn() -> reqwest::RequestBuilder
+) -> Result<String, EndpointError> {
+    let mut delay = FIRST_RETRY_DELAY;
+    let mut attempt = 0;
+    loop {
+        match send(options, request()).await {
+            Err(error) if error.is_retryable() && attempt < options.retries => {
+                attempt += 1;
+                let summary = error.to_string();
+                let summary = summary.lines().next().unwrap_or_default();
+                eprintln!("{}, retrying in {} second(s) ({} of {})", summary, delay.as_secs(), attempt, options.retries);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            result => return result
+        }
+    }
+}
+
+async fn send(options: &EndpointOptions, mut request: reqwest::RequestBuilder) -> Result<String, EndpointError> {
+    for (name, value) in &options.headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    let response_body = response.text().await?;
+    if status.is_success() {
+        Ok(response_body)
+    } else {
+        Err(EndpointError::Status { url: options.url.clone(), status, body: response_body })
+    }
+}
+
+fn truncate(text: &str, max_length: usize) -> String {
+    match text.char_indices().nth(max_length) {
+        Some((index, _)) => format!("{}... ({} more bytes)", &text[..index], text.len() - index),
+        None => text.to_string()
+    }
+}
+
+async fn post_query(
+    client: &reqwest::Client,
+    options: &EndpointOptions,
+    document: &str,
+    operation_name: &str,
+    variables: Value
+) -> Result<String, EndpointError> {
+    let input_body = GraphQLQuery {
+        query: document.to_string(),
+        operation_name: operation_name.to_string(),
+        variables
+    };
+    match send_with_retry(options, || client.post(&options.url).json(&input_body)).await {
+        Err(EndpointError::Status { status, body, .. }) if status.is_client_error() && has_graphql_errors(&body) => Ok(body),
+        result => result
+    }
+}
+
+/// GraphQL servers may answer a rejected query with a 4xx status, the errors
+/// in the body are more useful than the status alone.
+fn has_graphql_errors(response_body: &str) -> bool {
+    serde_json::from_str::<Value>(response_body)
+        .ok()
+        .and_then(|response| response.get("errors").cloned())
+        .is_some_and(|errors| errors.is_array())
+}
+
+/// Optional parts of the introspection query, newer than what some servers support.
+#[derive(Clone, Copy, PartialEq)]
+struct IntrospectionFeatures {
+    one_of: bool,
+    specified_by_url: bool,
+    /// Deprecated arguments and input fields, added to introspection in the
+    /// 2021 spec. Without it every argument and input field is reported as
+    /// not deprecated.
+    input_value_deprecation: bool
+}
+
+impl IntrospectionFeatures {
+    const ALL: IntrospectionFeatures = IntrospectionFeatures { one_of: true, specified_by_url: true, input_value_deprecation: true };
+
+    /// Turns off the features named in the errors of a rejected query, a
+    /// response with data was executed and rejects nothing.
+    fn without_rejected(self, response_body: &str) -> IntrospectionFeatures {
+        let response: RejectedQuery = match serde_json::from_str(response_body) {
+            Ok(response) => response,
+            Err(_) => return self
+        };
+        if !response.data.is_null() {
+            return self;
+        }
+        let mentions = |name: &str| response.errors.iter().any(|error| mentions_identifier(&error.message, name));
+        let rejects_input_value_deprecation = mentions("includeDeprecated")
+            || (mentions("__InputValue") && (mentions("isDeprecated") || mentions("deprecationReason")));
+        IntrospectionFeatures {
+            one_of: self.one_of && !mentions("isOneOf"),
+            specified_by_url: self.specified_by_url && !mentions("specifiedByURL"),
+            input_value_deprecation: self.input_value_deprecation && !rejects_input_value_deprecation
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RejectedQuery {
+    #[serde(default)]
+    data: Value,
+    #[serde(default)]
+    errors: Vec<QueryError>
+}
+
+#[derive(Deserialize)]
+struct QueryError {
+    message: String
+}
+
+/// Servers quote names differently, e.g. `"isOneOf"`, `'isOneOf'` or
+/// `` `isOneOf` ``, so the message is compared by whole identifiers.
+fn mentions_identifier(message: &str, name: &str) -> bool {
+    message
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|identifier| identifier == name)
+}
+
+fn introspection_document(type_ref_depth: usize, features: IntrospectionFeatures) -> String {
+    let (one_of_spread, one_of_fragment) = if features.one_of {
+        ("...OneOf", ONE_OF_FRAGMENT)
+    } else {
+        ("", "")
+    };
+    let (specified_by_url_spread, specified_by_url_fragment) = if features.specified_by_url {
+        ("...SpecifiedByUrl", SPECIFIED_BY_URL_FRAGMENT)
+    } else {
+        ("", "")
+    };
+    let (input_value_arguments, input_value_spread, input_value_fragment) = if features.input_value_deprecation {
+        ("(includeDeprecated: true)", "...InputValueDeprecation", INPUT_VALUE_DEPRECATION_FRAGMENT)
+    } else {
+        ("", "", "")
+    };
+    let query = include_str!("../resources/introspect.gql")
+        .replace(ONE_OF_PLACEHOLDER, one_of_spread)
+        .replace(SPECIFIED_BY_URL_PLACEHOLDER, specified_by_url_spread)
+        .replace(INPUT_VALUE_ARGUMENTS_PLACEHOLDER, input_value_arguments)
+        .replace(INPUT_VALUE_DEPRECATION_PLACEHOLDER, input_value_spread);
+    format!(
+        "{}\n{}{}{}{}",
+        query,
+        one_of_fragment,
+        specified_by_url_fragment,
+        input_value_fragment,
+        type_ref_fragment(type_ref_depth)
+    )
+}
+
+fn type_ref_fragment(depth: usize) -> String {
+    let mut selection = String::from("kind name");
+    for _ in 0..depth {
+        selection = format!("kind name ofType {{ {} }}", selection);
+    }
+    format!("fragment TypeRef on __Type {{ {} }}\n", selection)
+}
+
+fn replace_type(response: &mut Value, full_type: Value) {
+    let types_pointer = if response.pointer("/data/__schema/types").is_some() {
+        "/data/__schema/types"
+    } else {
+        "/__schema/types"
+    };
+    let types = match response.pointer_mut(types_pointer).and_then(Value::as_array_mut) {
+        Some(types) => types,
+        None => return
+    };
+    let existing = types
+        .iter_mut()
+        .find(|existing| existing.get("name") == full_type.get("name"));
+    if let Some(existing) = existing {
+        *existing = full_type;
+    }
+}
+
+#[derive(Serialize)]
+struct GraphQLQuery {
+    query: String,
+    #[serde(rename = "operationName")]
+    operation_name: String,
+    variables: Value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_rejected_one_of_from_error_messages() {
+        let graphql_js = r#"{"errors":[{"message":"Cannot query field \"isOneOf\" on type \"__Type\"."}]}"#;
+        let graphql_java = r#"{"data":null,"errors":[{"message":"Validation error (FieldUndefined) : Field 'isOneOf' in type '__Type' is undefined"}]}"#;
+        for body in [graphql_js, graphql_java] {
+            assert!(!IntrospectionFeatures::ALL.without_rejected(body).one_of);
+        }
+    }
+
+    #[test]
+    fn keeps_features_when_not_rejected() {
+        let executed = r#"{"data":{"__schema":{}},"errors":[{"message":"isOneOf failed"}]}"#;
+        let unrelated = r#"{"errors":[{"message":"Field \"isOneOfThem\" is unknown"}]}"#;
+        let not_json = "isOneOf";
+        for body in [executed, unrelated, not_json] {
+            assert!(IntrospectionFeatures::ALL.without_rejected(body) == IntrospectionFeatures::ALL);
+        }
+    }
+
+    #[test]
+    fn leaves_out_disabled_fragments() {
+        let with_one_of = introspection_document(1, IntrospectionFeatures::ALL);
+        assert!(with_one_of.contains("...OneOf") && with_one_of.contains(ONE_OF_FRAGMENT));
+        let without_one_of = introspection_document(1, IntrospectionFeatures { one_of: false, ..IntrospectionFeatures::ALL });
+        assert!(!without_one_of.contains("OneOf") && !without_one_of.contains(ONE_OF_PLACEHOLDER));
+    }
+
+    #[test]
+    fn detects_rejected_input_value_deprecation() {
+        let unknown_argument = r#"{"errors":[{"message":"Unknown argument \"includeDeprecated\" on field \"__Type.inputFields\"."}]}"#;
+        let unknown_field = r#"{"data":null,"errors":[{"message":"Validation error (FieldUndefined) : Field 'isDeprecated' in type '__InputValue' is undefined"}]}"#;
+        for body in [unknown_argument, unknown_field] {
+            let features = IntrospectionFeatures::ALL.without_rejected(body);
+            assert!(features.one_of && !features.input_value_deprecation);
+        }
+    }
+
+    #[test]
+    fn detects_rejected_specified_by_url() {
+        let body = r#"{"errors":[{"message":"Cannot query field \"specifiedByURL\" on type \"__Type\"."}]}"#;
+        let features = IntrospectionFeatures::ALL.without_rejected(body);
+        assert!(features.one_of && !features.specified_by_url);
+        let document = introspection_document(1, features);
+        assert!(!document.contains("SpecifiedByUrl") && !document.contains(SPECIFIED_BY_URL_PLACEHOLDER));
+    }
+
+    #[test]
+    fn leaves_out_input_value_deprecation() {
+        let with_deprecation = introspection_document(1, IntrospectionFeatures::ALL);
+        assert!(with_deprecation.contains("inputFields(includeDeprecated: true)") && with_deprecation.contains(INPUT_VALUE_DEPRECATION_FRAGMENT));
+        let document = introspection_document(1, IntrospectionFeatures { input_value_deprecation: false, ..IntrospectionFeatures::ALL });
+        assert!(!document.contains("inputFields(") && !document.contains("args("));
+        assert!(!document.contains("InputValueDeprecation") && !document.contains("__INPUT_VALUE"));
+        assert!(document.contains("fields(includeDeprecated: true)") && document.contains("enumValues(includeDeprecated: true)"));
+    }
+}
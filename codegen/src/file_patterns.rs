@@ -0,0 +1,192 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use globset::{Glob, GlobBuilder, GlobMatcher};
+
+const GLOB_META_CHARS: &[char] = &['*', '?', '[', '{'];
+/// Left out of `**` walks unless the pattern names them.
+const SKIPPED_DIRECTORIES: &[&str] = &[".git", "node_modules"];
+
+pub fn expand(patterns: &[String]) -> Result<Vec<PathBuf>, io::Error> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for pattern in patterns {
+        let matched = if pattern.contains(GLOB_META_CHARS) {
+            expand_glob(pattern)?
+        } else {
+            vec![PathBuf::from(pattern)]
+        };
+        if matched.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No files matched pattern {}", pattern)
+            ));
+        }
+        for path in matched {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, io::Error> {
+    let pattern = normalize_pattern(pattern);
+    let matcher = build_matcher(pattern)?;
+    let (base_directory, remainder) = split_literal_prefix(pattern);
+    // Without `**` a match is never deeper than the separators in the pattern
+    let max_depth = if remainder.contains("**") {
+        None
+    } else {
+        Some(remainder.matches('/').count() + 1)
+    };
+    let skipped: Vec<&str> = SKIPPED_DIRECTORIES
+        .iter()
+        .copied()
+        .filter(|directory| !pattern.contains(directory))
+        .collect();
+    let mut matched: Vec<PathBuf> = Vec::new();
+    walk_directory(&base_directory, max_depth, &skipped, &mut |path| {
+        if matcher.is_match(normalize_path(path)) {
+            matched.push(path.to_path_buf());
+        }
+    })?;
+    matched.sort();
+    Ok(matched)
+}
+
+/// `./schema/*.graphql` and `schema/*.graphql` name the same files, the
+/// leading `./` is removed from patterns and walked paths alike.
+fn normalize_pattern(pattern: &str) -> &str {
+    let mut pattern = pattern;
+    while let Some(rest) = pattern.strip_prefix("./") {
+        pattern = rest.trim_start_matches('/');
+    }
+    pattern
+}
+
+fn normalize_path(path: &Path) -> &Path {
+    path.strip_prefix(".").unwrap_or(path)
+}
+
+fn build_matcher(pattern: &str) -> Result<GlobMatcher, io::Error> {
+    let glob: Glob = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error.to_string()))?;
+    Ok(glob.compile_matcher())
+}
+
+/// Splits the pattern into the directory before the first glob character,
+/// where walking starts, and the rest of the pattern below that directory.
+fn split_literal_prefix(pattern: &str) -> (PathBuf, &str) {
+    let literal_prefix = match pattern.find(GLOB_META_CHARS) {
+        Some(index) => &pattern[..index],
+        None => pattern
+    };
+    match literal_prefix.rfind('/') {
+        Some(index) if index > 0 => (PathBuf::from(&literal_prefix[..index]), &pattern[index + 1..]),
+        Some(_) => (PathBuf::from("/"), &pattern[1..]),
+        None => (PathBuf::from("."), pattern)
+    }
+}
+
+fn walk_directory(
+    directory: &Path,
+    max_depth: Option<usize>,
+    skipped: &[&str],
+    on_file: &mut dyn FnMut(&Path)
+) -> Result<(), io::Error> {
+    if !directory.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let is_skipped = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| skipped.contains(&name));
+            match max_depth {
+                _ if is_skipped => (),
+                Some(max_depth) if max_depth > 1 => walk_directory(&path, Some(max_depth - 1), skipped, on_file)?,
+                Some(_) => (),
+                None => walk_directory(&path, None, skipped, on_file)?
+            }
+        } else {
+            on_file(&path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_files(root: &Path, files: &[&str]) {
+        let _ = fs::remove_dir_all(root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+    }
+
+    fn relative_names(root: &Path, paths: Vec<PathBuf>) -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| normalize_path(path).strip_prefix(normalize_path(root)).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn expands_recursive_pattern() {
+        let root = std::env::temp_dir().join(format!("file-patterns-recursive-{}", std::process::id()));
+        create_files(&root, &["a.graphql", "nested/b.graphql", "nested/deeper/c.graphql", "nested/d.txt"]);
+        let matched = expand_glob(&format!("{}/**/*.graphql", root.display())).unwrap();
+        assert_eq!(relative_names(&root, matched), ["a.graphql", "nested/b.graphql", "nested/deeper/c.graphql"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn single_star_does_not_descend() {
+        let root = std::env::temp_dir().join(format!("file-patterns-single-{}", std::process::id()));
+        create_files(&root, &["a.graphql", "nested/b.graphql"]);
+        let matched = expand_glob(&format!("{}/*.graphql", root.display())).unwrap();
+        assert_eq!(relative_names(&root, matched), ["a.graphql"]);
+        let matched = expand_glob(&format!("{}/*/*.graphql", root.display())).unwrap();
+        assert_eq!(relative_names(&root, matched), ["nested/b.graphql"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn skips_node_modules_unless_named() {
+        let root = std::env::temp_dir().join(format!("file-patterns-skipped-{}", std::process::id()));
+        create_files(&root, &["a.graphql", "node_modules/lib/b.graphql", ".git/c.graphql"]);
+        let matched = expand_glob(&format!("{}/**/*.graphql", root.display())).unwrap();
+        assert_eq!(relative_names(&root, matched), ["a.graphql"]);
+        let matched = expand_glob(&format!("{}/node_modules/**/*.graphql", root.display())).unwrap();
+        assert_eq!(relative_names(&root, matched), ["node_modules/lib/b.graphql"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn leading_dot_slash_matches() {
+        let root = PathBuf::from(format!("target/file-patterns-dot-{}", std::process::id()));
+        create_files(&root, &["schema/a.graphql", "schema/nested/b.graphql"]);
+        let matched = expand_glob(&format!("./{}/schema/**/*.graphql", root.display())).unwrap();
+        assert_eq!(relative_names(&root, matched), ["schema/a.graphql", "schema/nested/b.graphql"]);
+        let matched = expand_glob(&format!("./{}/schema/*.graphql", root.display())).unwrap();
+        assert_eq!(relative_names(&root, matched), ["schema/a.graphql"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn splits_literal_prefix() {
+        assert_eq!(split_literal_prefix("schema/**/*.graphql"), (PathBuf::from("schema"), "**/*.graphql"));
+        assert_eq!(split_literal_prefix("*.graphql"), (PathBuf::from("."), "*.graphql"));
+        assert_eq!(split_literal_prefix("/*.graphql"), (PathBuf::from("/"), "*.graphql"));
+        assert_eq!(normalize_pattern("././schema/*.graphql"), "schema/*.graphql");
+    }
+}
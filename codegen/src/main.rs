@@ -1,3 +1,4 @@
+use std::env;
 use std::io;
 use std::io::Read;
 use std::fs;
@@ -5,21 +6,37 @@ use std::fs::File;
 use std::process;
 use std::path::PathBuf;
 use std::collections::HashMap;
-use clap::Parser;
+use std::time::Duration;
+use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
-use crate::schema::GqlDocument;
+use crate::schema::{GqlDocument, SchemaSource, SortOrder};
 use crate::code_writer::CodeFileOptions;
+use crate::code_generator::SchemaSnapshot;
+use crate::endpoint::{ClientCertificate, EndpointOptions, TlsOptions};
+use crate::command::CommandOptions;
+use crate::diagnostics::Diagnostic;
+use crate::schema_diff::ChangeKind;
+use futures::future;
 
 mod code_generator;
 mod code_writer;
+mod command;
+mod diagnostics;
+mod endpoint;
+mod file_patterns;
 mod schema;
 mod schema_sdl;
 mod schema_introspection;
+mod schema_printer;
+mod schema_diff;
 
 const DEFAULT_CONFIG_PATH: &str = "graphql-freeze.json";
 const DEFAULT_RUNTIME: &str = "graphql-freeze";
 const DEFAULT_INDENT: &str = "    ";
 const DEFAULT_PROFILE_NAME: &str = "default";
+const STDIN_SOURCE: &str = "-";
+const PROFILE_SOURCE_PREFIX: &str = "profile:";
+const DIFF_FAILED_EXIT_CODE: i32 = 2;
 
 #[tokio::main]
 async fn main() {
@@ -46,53 +63,79 @@ async fn main() {
         .cloned()
         .unwrap_or_else(default_line_break);
 
-    let output_directory: String = if let Some(output) = args.output {
-        output
+    let exclude_deprecated = config
+        .as_ref()
+        .and_then(|c| c.exclude_deprecated)
+        .unwrap_or(false);
+
+    let include_introspection_types = config
+        .as_ref()
+        .and_then(|c| c.include_introspection_types)
+        .unwrap_or(false);
+
+    let type_order = config
+        .as_ref()
+        .and_then(|c| c.type_order)
+        .unwrap_or(SortOrder::Alphabetical);
+
+    let field_order = config
+        .as_ref()
+        .and_then(|c| c.field_order)
+        .unwrap_or(SortOrder::Declaration);
+
+    let strict = args.strict || config
+        .as_ref()
+        .and_then(|c| c.strict)
+        .unwrap_or(false);
+
+    let schema_snapshot = args.schema_snapshot.or(config
+        .as_ref()
+        .and_then(|c| c.schema_snapshot));
+
+    match &args.command {
+        Some(CliCommand::PrintSchema) => {
+            let (fetch, process) = resolve_schema_source(&args, &config);
+            let schema_options = SchemaOptions { include_introspection_types, type_order, field_order, strict };
+            let write_options = CodeFileOptions { indent, line_break };
+            print_schema(fetch, process, schema_options, write_options, args.dump_on_parse_error).await;
+            return;
+        }
+        Some(CliCommand::Diff { old, new, fail_on_dangerous }) => {
+            let old_source = resolve_location(old, &args, &config);
+            let new_source = match new {
+                Some(new) => resolve_location(new, &args, &config),
+                None => resolve_schema_source(&args, &config)
+            };
+            let schema_options = SchemaOptions { include_introspection_types, type_order, field_order, strict };
+            diff_schemas(old_source, new_source, schema_options, *fail_on_dangerous, args.dump_on_parse_error).await;
+            return;
+        }
+        None => ()
+    }
+
+    let (fetch, process) = resolve_schema_source(&args, &config);
+
+    let output_directory: String = if let Some(output) = &args.output {
+        output.to_string()
     } else if let Some(output_dir) = &config.as_ref().and_then(|c| c.output_directory.as_ref()) {
         output_dir.to_string()
     } else {
         exit_with_error("No output directory was given")
     };
 
-    let (fetch, process): (FetchMethod, ProcessMethod) = if let Some(url) = args.url {
-        (FetchMethod::Endpoint { url }, ProcessMethod::Introspection)
-    } else if let Some(file) = args.file {
-        (FetchMethod::File { path: PathBuf::from(file) }, ProcessMethod::Sdl)
-    } else if let Some(config) = &config {
-        let profile_name: String = args.profile.unwrap_or(DEFAULT_PROFILE_NAME.to_string());
-        if let Some(profiles) = &config.profiles {
-            let profile = profiles.get(&profile_name);
-            match profile {
-                Some(profile) => {
-                    match profile {
-                        ConfigProfile::Endpoint { url } => {
-                            (FetchMethod::Endpoint { url: url.to_string() }, ProcessMethod::Introspection)
-                        }
-                        ConfigProfile::File { path } => {
-                            (FetchMethod::File { path: PathBuf::from(path) }, ProcessMethod::Sdl)
-                        }
-                        ConfigProfile::PipeIntrospection => {
-                            (FetchMethod::Pipe, ProcessMethod::Introspection)
-                        }
-                        ConfigProfile::PipeSdl => {
-                            (FetchMethod::Pipe, ProcessMethod::Sdl)
-                        }
-                    }
-                }
-                None => exit_with_error(&format!("No profile named \"{}\"", profile_name))
-            }
-        } else {
-            exit_with_error("No method to fetch schema was provided and default profile is not defined in config file")
-        }
-    } else {
-        exit_with_error("No method to fetch schema was provided, use --url, --file or make a config")
-    };
+    if let (Some(SchemaFormat::Introspection), ProcessMethod::Sdl) = (schema_snapshot, &process) {
+        exit_with_error("Introspection schema snapshot requires the schema to be read as an introspection result");
+    }
 
     let options = CodegenOptions {
         runtime_package,
         indent,
         line_break,
         output_directory: PathBuf::from(output_directory),
+        exclude_deprecated,
+        schema: SchemaOptions { include_introspection_types, type_order, field_order, strict },
+        schema_snapshot,
+        check: args.check,
         fetch,
         process
     };
@@ -100,6 +143,109 @@ async fn main() {
     execute(options, args.dump_on_parse_error).await;
 }
 
+fn resolve_schema_source(args: &Cli, config: &Option<CodegenJsonConfig>) -> (FetchMethod, ProcessMethod) {
+    if let Some(url) = args.url.clone() {
+        let options = to_endpoint_options(url, cli_headers(args), cli_network(args));
+        (FetchMethod::Endpoint { options }, ProcessMethod::Introspection)
+    } else if !args.file.is_empty() {
+        (FetchMethod::File { patterns: args.file.clone() }, ProcessMethod::Sdl)
+    } else if let Some(path) = args.introspection_file.clone() {
+        (FetchMethod::SingleFile { path: PathBuf::from(path) }, ProcessMethod::Introspection)
+    } else if let Some(config) = config {
+        let profile_name: String = args.profile.clone().unwrap_or(DEFAULT_PROFILE_NAME.to_string());
+        if config.profiles.is_none() {
+            exit_with_error("No method to fetch schema was provided and default profile is not defined in config file")
+        }
+        resolve_profile(&profile_name, args, config)
+    } else {
+        exit_with_error("No method to fetch schema was provided, use --url, --file, --introspection-file or make a config")
+    }
+}
+
+/// Resolves a profile from the config file, headers and network settings
+/// given on the command line take precedence over the profile.
+fn resolve_profile(profile_name: &str, args: &Cli, config: &CodegenJsonConfig) -> (FetchMethod, ProcessMethod) {
+    let cli_headers = cli_headers(args);
+    let cli_network = cli_network(args);
+    let profile = config.profiles.as_ref().and_then(|profiles| profiles.get(profile_name));
+    match profile {
+        Some(ConfigProfile::Endpoint(endpoint)) => {
+            let headers = profile_headers(&endpoint.headers, cli_headers, profile_name);
+            let network = cli_network.or(endpoint.network());
+            let mut options = to_endpoint_options(endpoint.url.clone(), headers, network);
+            endpoint.apply_connection(&mut options, profile_name);
+            (FetchMethod::Endpoint { options }, ProcessMethod::Introspection)
+        }
+        Some(ConfigProfile::SdlUrl(endpoint)) => {
+            let headers = profile_headers(&endpoint.headers, cli_headers, profile_name);
+            let network = cli_network.or(endpoint.network());
+            let mut options = to_endpoint_options(endpoint.url.clone(), headers, network);
+            endpoint.apply_connection(&mut options, profile_name);
+            (FetchMethod::SdlUrl { options }, ProcessMethod::Sdl)
+        }
+        Some(ConfigProfile::File { path }) => {
+            (FetchMethod::File { patterns: path.to_vec() }, ProcessMethod::Sdl)
+        }
+        Some(ConfigProfile::IntrospectionFile { path }) => {
+            (FetchMethod::SingleFile { path: PathBuf::from(path) }, ProcessMethod::Introspection)
+        }
+        Some(ConfigProfile::Command { command, args, format }) => {
+            let options = CommandOptions { program: command.clone(), args: args.clone().unwrap_or_default() };
+            let process = match format {
+                Some(SchemaFormat::Introspection) => ProcessMethod::Introspection,
+                Some(SchemaFormat::Sdl) | None => ProcessMethod::Sdl
+            };
+            (FetchMethod::Command { options }, process)
+        }
+        Some(ConfigProfile::PipeIntrospection) => {
+            (FetchMethod::Pipe, ProcessMethod::Introspection)
+        }
+        Some(ConfigProfile::PipeSdl) => {
+            (FetchMethod::Pipe, ProcessMethod::Sdl)
+        }
+        None => exit_with_error(&format!("No profile named \"{}\"", profile_name))
+    }
+}
+
+/// Resolves a schema given directly on the command line: a `profile:<name>`
+/// from the config file, an endpoint url, `-` for stdin, a `.json`
+/// introspection file or SDL files and glob patterns.
+fn resolve_location(location: &str, args: &Cli, config: &Option<CodegenJsonConfig>) -> (FetchMethod, ProcessMethod) {
+    if let Some(profile_name) = location.strip_prefix(PROFILE_SOURCE_PREFIX) {
+        match config {
+            Some(config) => resolve_profile(profile_name, args, config),
+            None => exit_with_error(&format!("Unable to use profile \"{}\", no config file was found", profile_name))
+        }
+    } else if location.starts_with("http://") || location.starts_with("https://") {
+        let options = to_endpoint_options(location.to_string(), cli_headers(args), cli_network(args));
+        (FetchMethod::Endpoint { options }, ProcessMethod::Introspection)
+    } else if location == STDIN_SOURCE {
+        (FetchMethod::Pipe, ProcessMethod::Detect)
+    } else if location.ends_with(".json") {
+        (FetchMethod::SingleFile { path: PathBuf::from(location) }, ProcessMethod::Introspection)
+    } else {
+        (FetchMethod::File { patterns: vec![location.to_string()] }, ProcessMethod::Sdl)
+    }
+}
+
+fn cli_headers(args: &Cli) -> Vec<(String, String)> {
+    args.header
+        .iter()
+        .map(|header| match parse_header(header) {
+            Some(header) => header,
+            None => exit_with_error(&format!("Invalid header \"{}\", expected format is \"Name: value\"", header))
+        })
+        .collect()
+}
+
+fn cli_network(args: &Cli) -> NetworkSettings {
+    NetworkSettings {
+        connect_timeout: args.connect_timeout,
+        timeout: args.timeout,
+        retries: args.retries
+    }
+}
+
 fn read_config_from_args(args: &Cli) -> Option<CodegenJsonConfig> {
     match &args.config {
         Some(path) => {
@@ -143,18 +289,51 @@ fn read_config(path: &str) -> Result<Option<CodegenJsonConfig>, std::io::Error>
 
 #[derive(Parser)]
 struct Cli {
-    #[arg(short, long, help = "Path to config file from working directory, default: graphql-freeze.json")]
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    #[arg(short, long, global = true, help = "Path to config file from working directory, default: graphql-freeze.json")]
     config: Option<String>,
-    #[arg(short, long, help = "Profile used from config file, default: default")]
+    #[arg(short, long, global = true, help = "Profile used from config file, default: default")]
     profile: Option<String>,
-    #[arg(short, long, help = "Generates client from introspection, override config file")]
+    #[arg(short, long, global = true, conflicts_with_all = ["file", "introspection_file"], help = "Generates client from introspection, override config file")]
     url: Option<String>,
-    #[arg(short, long, help = "Generates client from SDL in file, override config file")]
-    file: Option<String>,
+    #[arg(short, long, num_args = 1.., global = true, conflicts_with = "introspection_file", help = "Generates client from SDL in one or more files or glob patterns, override config file")]
+    file: Vec<String>,
+    #[arg(short, long, global = true, help = "Generates client from a JSON introspection result in a file, override config file")]
+    introspection_file: Option<String>,
+    #[arg(short = 'H', long, global = true, help = "Header sent with the introspection request as \"Name: value\", can be repeated")]
+    header: Vec<String>,
+    #[arg(long, global = true, help = "Seconds to wait for a connection to the endpoint, override config file, default: 10")]
+    connect_timeout: Option<u64>,
+    #[arg(long, global = true, help = "Seconds to wait for each endpoint request to complete, override config file, default: 60")]
+    timeout: Option<u64>,
+    #[arg(long, global = true, help = "Times a failed endpoint request is retried with increasing delay, override config file, default: 0")]
+    retries: Option<u32>,
     #[arg(short, long, help = "Output directory, override config file")]
     output: Option<String>,
-    #[arg(short = 'e', long = "errdump", default_value_t = false, help = "Print out the contents to stderr on schema parse error, useful for troubleshooting")]
-    dump_on_parse_error: bool
+    #[arg(short = 'e', long = "errdump", default_value_t = false, global = true, help = "Print out the contents to stderr on schema parse error, useful for troubleshooting")]
+    dump_on_parse_error: bool,
+    #[arg(long, default_value_t = false, global = true, help = "Fail when parts of the schema are skipped in generated code, override config file")]
+    strict: bool,
+    #[arg(long, default_value_t = false, help = "Compare generated files with the output directory without writing, fail if any would change")]
+    check: bool,
+    #[arg(long, value_enum, help = "Write the schema to the output directory as schema.graphql (sdl) or schema.json (introspection), override config file")]
+    schema_snapshot: Option<SchemaFormat>
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    #[command(about = "Print the schema as SDL to stdout instead of generating code")]
+    PrintSchema,
+    #[command(about = "Compare two schemas and report breaking, dangerous and safe changes")]
+    Diff {
+        #[arg(help = "Old schema: SDL file or glob pattern, .json introspection file, endpoint url, profile:<name> or - for stdin")]
+        old: String,
+        #[arg(help = "New schema in the same forms as old, default: schema from the url, file or profile options")]
+        new: Option<String>,
+        #[arg(long, default_value_t = false, help = "Also exit with failure when dangerous changes are found")]
+        fail_on_dangerous: bool
+    }
 }
 
 fn default_line_break() -> String {
@@ -175,6 +354,17 @@ struct CodegenJsonConfig {
     line_break: Option<String>,
     indent: Option<String>,
     runtime: Option<String>,
+    #[serde(rename = "excludeDeprecated")]
+    exclude_deprecated: Option<bool>,
+    #[serde(rename = "includeIntrospectionTypes")]
+    include_introspection_types: Option<bool>,
+    #[serde(rename = "typeOrder")]
+    type_order: Option<SortOrder>,
+    #[serde(rename = "fieldOrder")]
+    field_order: Option<SortOrder>,
+    strict: Option<bool>,
+    #[serde(rename = "schemaSnapshot")]
+    schema_snapshot: Option<SchemaFormat>,
 }
 
 #[derive(Deserialize)]
@@ -182,60 +372,305 @@ struct CodegenJsonConfig {
 #[serde(tag = "method")]
 enum ConfigProfile {
     #[serde(rename = "endpoint")]
-    Endpoint { url: String },
+    Endpoint(EndpointConfig),
+    #[serde(rename = "sdlUrl")]
+    SdlUrl(EndpointConfig),
     #[serde(rename = "file")]
-    File { path: String },
+    File { path: ConfigPaths },
+    #[serde(rename = "introspectionFile")]
+    IntrospectionFile { path: String },
+    #[serde(rename = "command")]
+    Command { command: String, args: Option<Vec<String>>, format: Option<SchemaFormat> },
     #[serde(rename = "pipeIntrospection")]
     PipeIntrospection,
     #[serde(rename = "pipeSdl")]
     PipeSdl
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EndpointConfig {
+    url: String,
+    headers: Option<HashMap<String, String>>,
+    #[serde(rename = "connectTimeout")]
+    connect_timeout: Option<u64>,
+    timeout: Option<u64>,
+    retries: Option<u32>,
+    proxy: Option<String>,
+    #[serde(rename = "caCertificates")]
+    ca_certificates: Option<ConfigPaths>,
+    #[serde(rename = "clientCertificate")]
+    client_certificate: Option<String>,
+    #[serde(rename = "clientKey")]
+    client_key: Option<String>,
+    #[serde(rename = "acceptInvalidCertificates")]
+    accept_invalid_certificates: Option<bool>
+}
+
+impl EndpointConfig {
+    fn apply_connection(&self, options: &mut EndpointOptions, profile_name: &str) {
+        let client_certificate = match (&self.client_certificate, &self.client_key) {
+            (Some(certificate), Some(key)) => Some(ClientCertificate {
+                certificate: PathBuf::from(certificate),
+                key: PathBuf::from(key)
+            }),
+            (None, None) => None,
+            _ => exit_with_error(&format!("Profile \"{}\" must set both clientCertificate and clientKey", profile_name))
+        };
+        let root_certificates = self.ca_certificates
+            .iter()
+            .flat_map(|paths| paths.to_vec())
+            .map(PathBuf::from)
+            .collect();
+        options.proxy = self.proxy.clone();
+        options.tls = TlsOptions {
+            root_certificates,
+            client_certificate,
+            accept_invalid_certificates: self.accept_invalid_certificates.unwrap_or(false)
+        };
+    }
+
+    fn network(&self) -> NetworkSettings {
+        NetworkSettings {
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            retries: self.retries
+        }
+    }
+}
+
+/// Timeouts in seconds and retry count, each unset value falls back to the
+/// next source and finally to the defaults in `endpoint`.
+#[derive(Clone, Copy)]
+struct NetworkSettings {
+    connect_timeout: Option<u64>,
+    timeout: Option<u64>,
+    retries: Option<u32>
+}
+
+impl NetworkSettings {
+    fn or(self, fallback: NetworkSettings) -> NetworkSettings {
+        NetworkSettings {
+            connect_timeout: self.connect_timeout.or(fallback.connect_timeout),
+            timeout: self.timeout.or(fallback.timeout),
+            retries: self.retries.or(fallback.retries)
+        }
+    }
+}
+
+fn to_endpoint_options(url: String, headers: Vec<(String, String)>, network: NetworkSettings) -> EndpointOptions {
+    let connect_timeout = network.connect_timeout.unwrap_or(endpoint::DEFAULT_CONNECT_TIMEOUT_SECONDS);
+    let timeout = network.timeout.unwrap_or(endpoint::DEFAULT_TIMEOUT_SECONDS);
+    EndpointOptions {
+        url,
+        headers,
+        connect_timeout: Duration::from_secs(connect_timeout),
+        timeout: Duration::from_secs(timeout),
+        retries: network.retries.unwrap_or(0),
+        proxy: None,
+        tls: TlsOptions::default()
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, ValueEnum)]
+enum SchemaFormat {
+    #[serde(rename = "sdl")]
+    Sdl,
+    #[serde(rename = "introspection")]
+    Introspection
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigPaths {
+    Single(String),
+    Multiple(Vec<String>)
+}
+
+impl ConfigPaths {
+    fn to_vec(&self) -> Vec<String> {
+        match self {
+            ConfigPaths::Single(path) => vec![path.clone()],
+            ConfigPaths::Multiple(paths) => paths.clone()
+        }
+    }
+}
+
 async fn execute(options: CodegenOptions, show_schema_on_error: bool) {
-    let raw_content = match options.fetch {
-        FetchMethod::Endpoint { url } => {
-            match read_endpoint(&url).await {
-                Ok(response) => response,
-                Err(error) => {
-                    eprintln!("Networking error {}", error);
-                    process::exit(1)
-                }
+    let (mut document, sources) = read_document(options.fetch, options.process, &options.schema, show_schema_on_error).await;
+    let write_options = CodeFileOptions {
+        indent: options.indent,
+        line_break: options.line_break
+    };
+    let snapshot = options.schema_snapshot.map(|format| match format {
+        SchemaFormat::Sdl => SchemaSnapshot::Sdl(schema_printer::print_document(&document, &write_options)),
+        SchemaFormat::Introspection => SchemaSnapshot::Introspection(pretty_json(&sources[0].content, &write_options))
+    });
+    if options.exclude_deprecated {
+        document.remove_deprecated();
+    }
+    if options.check {
+        let up_to_date = code_generator::check_files(&document, &options.output_directory, &write_options, &options.runtime_package, &snapshot);
+        if !up_to_date {
+            exit_with_error("Generated files are out of date, run graphql-freeze without --check to regenerate them");
+        }
+        return;
+    }
+    code_generator::write_files(document, options.output_directory, write_options, &options.runtime_package, snapshot).await;
+}
+
+async fn print_schema(
+    fetch: FetchMethod,
+    process: ProcessMethod,
+    schema_options: SchemaOptions,
+    write_options: CodeFileOptions,
+    show_schema_on_error: bool
+) {
+    let (document, _) = read_document(fetch, process, &schema_options, show_schema_on_error).await;
+    print!("{}", schema_printer::print_document(&document, &write_options));
+}
+
+async fn diff_schemas(
+    old: (FetchMethod, ProcessMethod),
+    new: (FetchMethod, ProcessMethod),
+    schema_options: SchemaOptions,
+    fail_on_dangerous: bool,
+    show_schema_on_error: bool
+) {
+    if let (FetchMethod::Pipe, FetchMethod::Pipe) = (&old.0, &new.0) {
+        exit_with_error("Only one of the schemas can be read from stdin");
+    }
+    let ((old_document, _), (new_document, _)) = future::join(
+        read_document(old.0, old.1, &schema_options, show_schema_on_error),
+        read_document(new.0, new.1, &schema_options, show_schema_on_error)
+    ).await;
+    let changes = schema_diff::diff(&old_document, &new_document);
+    schema_diff::print(&changes);
+    let failed = changes.iter().any(|change| match change.kind {
+        ChangeKind::Breaking => true,
+        ChangeKind::Dangerous => fail_on_dangerous,
+        ChangeKind::Safe => false
+    });
+    if failed {
+        process::exit(DIFF_FAILED_EXIT_CODE)
+    }
+}
+
+/// Fetches and parses the schema, aborting on errors, then applies the
+/// options shared by every command. Also returns the sources it was read from.
+async fn read_document(
+    fetch: FetchMethod,
+    process: ProcessMethod,
+    options: &SchemaOptions,
+    show_schema_on_error: bool
+) -> (GqlDocument, Vec<SchemaSource>) {
+    let sources: Vec<SchemaSource> = match fetch {
+        FetchMethod::Endpoint { options } => {
+            match endpoint::read_introspection(&options).await {
+                Ok(response) => vec![SchemaSource { name: Some(options.url), content: response }],
+                Err(error) => exit_with_error(&error.to_string())
+            }
+        },
+        FetchMethod::SdlUrl { options } => {
+            match endpoint::read_sdl(&options).await {
+                Ok(sdl) => vec![SchemaSource { name: Some(options.url), content: sdl }],
+                Err(error) => exit_with_error(&error.to_string())
             }
         },
-        FetchMethod::File { path } => {
-            match read_file(path).await {
-                Ok(file_content) => file_content,
+        FetchMethod::Command { options } => {
+            match command::read_output(&options).await {
+                Ok(output) => vec![SchemaSource { name: Some(options.display_name()), content: output }],
+                Err(error) => exit_with_error(&format!("Command \"{}\" failed. {}", options.display_name(), error))
+            }
+        },
+        FetchMethod::File { patterns } => {
+            match read_files(&patterns).await {
+                Ok(sources) => sources,
                 Err(error) => exit_with_error(&error.to_string())
             }
         },
-        FetchMethod::Pipe => read_pipe()
+        FetchMethod::SingleFile { path } => {
+            match read_file(&path).await {
+                Ok(content) => vec![SchemaSource { name: Some(path.display().to_string()), content }],
+                Err(error) => exit_with_error(&format!("Unable to read {}: {}", path.display(), error))
+            }
+        },
+        FetchMethod::Pipe => vec![SchemaSource { name: None, content: read_pipe() }]
+    };
+    let format = match process {
+        ProcessMethod::Sdl => SchemaFormat::Sdl,
+        ProcessMethod::Introspection => SchemaFormat::Introspection,
+        ProcessMethod::Detect if looks_like_json(&sources) => SchemaFormat::Introspection,
+        ProcessMethod::Detect => SchemaFormat::Sdl
     };
-    let document: GqlDocument = match options.process {
-        ProcessMethod::Introspection => {
-            match schema_introspection::from_response_body(&raw_content) {
-                Ok(schema) => schema,
-                Err(error) => abort_on_schema_parse_fail(show_schema_on_error, &raw_content, &error.to_string())
+    let (mut document, warnings) = match format {
+        SchemaFormat::Introspection => {
+            let raw_content = match sources.as_slice() {
+                [source] => &source.content,
+                _ => exit_with_error("Introspection response must be read from a single source")
+            };
+            match schema_introspection::from_response_body(raw_content) {
+                Ok(result) => result,
+                Err(error) => {
+                    let diagnostic = Diagnostic::error(diagnostics::INVALID_INTROSPECTION, error.to_string());
+                    abort_on_schema_parse_fail(show_schema_on_error, &sources, &[diagnostic])
+                }
             }
         },
-        ProcessMethod::Sdl => {
-            match schema_sdl::from_sdl_string(&raw_content) {
-                Ok(schema) => schema,
-                Err(error) => abort_on_schema_parse_fail(show_schema_on_error, &raw_content, &error.to_string())
+        SchemaFormat::Sdl => {
+            match schema_sdl::from_sdl_sources(&sources) {
+                Ok(result) => result,
+                Err(problems) => abort_on_schema_parse_fail(show_schema_on_error, &sources, &problems)
             }
         }
     };
-    let write_options = CodeFileOptions {
-        indent: options.indent,
-        line_break: options.line_break
+    let problems = document.validate();
+    if !problems.is_empty() {
+        let all: Vec<Diagnostic> = warnings.into_iter().chain(problems).collect();
+        abort_on_schema_parse_fail(show_schema_on_error, &sources, &all);
+    }
+    if !warnings.is_empty() {
+        diagnostics::print(&warnings, &sources);
+        if options.strict {
+            exit_with_error("Schema was read with warnings, failing in strict mode");
+        }
+    }
+    if !options.include_introspection_types {
+        document.remove_introspection_types();
+    }
+    document.sort(options.type_order, options.field_order);
+    (document, sources)
+}
+
+fn pretty_json(content: &str, options: &CodeFileOptions) -> String {
+    let value: serde_json::Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(error) => exit_with_error(&format!("Unable to read introspection result for schema snapshot: {}", error))
     };
-    code_generator::write_files(document, options.output_directory, write_options, &options.runtime_package).await;
+    let mut pretty: Vec<u8> = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(options.indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut pretty, formatter);
+    if let Err(error) = value.serialize(&mut serializer) {
+        exit_with_error(&format!("Unable to write schema snapshot: {}", error))
+    }
+    let pretty = String::from_utf8_lossy(&pretty);
+    format!("{}{}", pretty.replace('\n', &options.line_break), options.line_break)
+}
+
+fn looks_like_json(sources: &[SchemaSource]) -> bool {
+    sources.iter().all(|source| source.content.trim_start().starts_with('{'))
 }
 
-fn abort_on_schema_parse_fail(show_schema_on_error: bool, schema_content: &str, error_string: &str) -> ! {
-    eprintln!("{}", error_string);
+fn abort_on_schema_parse_fail(show_schema_on_error: bool, sources: &[SchemaSource], problems: &[Diagnostic]) -> ! {
+    diagnostics::print(problems, sources);
     if show_schema_on_error {
         eprintln!("Error parsing schema types");
-        eprintln!("{}", schema_content);
+        for source in sources {
+            if let Some(name) = &source.name {
+                eprintln!("# {}", name);
+            }
+            eprintln!("{}", source.content);
+        }
     } else {
         eprintln!("Error parsing schema, use --errdump to display the attempted schema to parse");
     }
@@ -247,42 +682,56 @@ struct CodegenOptions {
     line_break: String,
     indent: String,
     runtime_package: String,
+    exclude_deprecated: bool,
+    schema: SchemaOptions,
+    schema_snapshot: Option<SchemaFormat>,
+    check: bool,
     fetch: FetchMethod,
     process: ProcessMethod
 }
 
+struct SchemaOptions {
+    include_introspection_types: bool,
+    type_order: SortOrder,
+    field_order: SortOrder,
+    strict: bool
+}
+
 enum FetchMethod {
-    File { path: PathBuf },
-    Endpoint { url: String },
+    File { patterns: Vec<String> },
+    /// A file read as is, its path is not treated as a glob pattern.
+    SingleFile { path: PathBuf },
+    Endpoint { options: EndpointOptions },
+    SdlUrl { options: EndpointOptions },
+    Command { options: CommandOptions },
     Pipe,
 }
 
 enum ProcessMethod {
     Sdl,
-    Introspection
+    Introspection,
+    /// Introspection when the content is a JSON object, otherwise SDL.
+    Detect
 }
 
-async fn read_file(path: PathBuf) -> Result<String, io::Error> {
+async fn read_files(patterns: &[String]) -> Result<Vec<SchemaSource>, io::Error> {
+    let mut sources: Vec<SchemaSource> = Vec::new();
+    for path in file_patterns::expand(patterns)? {
+        let content = read_file(&path).await.map_err(|error| {
+            io::Error::new(error.kind(), format!("Unable to read {}: {}", path.display(), error))
+        })?;
+        sources.push(SchemaSource { name: Some(path.display().to_string()), content });
+    }
+    Ok(sources)
+}
+
+async fn read_file(path: &PathBuf) -> Result<String, io::Error> {
     let mut file = File::open(path)?;
     let mut content = String::new();
     file.read_to_string(&mut content)?;
     Ok(content)
 }
 
-async fn read_endpoint(url: &str) -> Result<String, reqwest::Error> {
-    let query = include_str!("../resources/introspect.gql");
-    let input_body = GraphQLQuery { query: query.to_string() };
-    let client = reqwest::Client::new();
-    let response = client
-            .post(url)
-            .json(&input_body)
-            .send()
-            .await?
-            .error_for_status()?;
-    let response_body = response.text().await?;
-    Ok(response_body)
-}
-
 fn read_pipe() -> String {
     let mut buffer = String::new();
     match io::stdin().read_to_string(&mut buffer) {
@@ -295,12 +744,107 @@ fn read_pipe() -> String {
     }
 }
 
-#[derive(Serialize)]
-struct GraphQLQuery {
-    query: String
+fn profile_headers(
+    headers: &Option<HashMap<String, String>>,
+    cli_headers: Vec<(String, String)>,
+    profile_name: &str
+) -> Vec<(String, String)> {
+    let mut all_headers: Vec<(String, String)> = Vec::new();
+    for (name, value) in headers.iter().flatten() {
+        match expand_env_vars(value) {
+            Ok(value) => all_headers.push((name.clone(), value)),
+            Err(error) => exit_with_error(&format!("Header {} in profile \"{}\": {}", name, profile_name, error))
+        }
+    }
+    // A header given on the command line replaces the profile header with the same name
+    all_headers.retain(|(name, _)| !cli_headers.iter().any(|(cli_name, _)| cli_name.eq_ignore_ascii_case(name)));
+    all_headers.extend(cli_headers);
+    all_headers
+}
+
+fn parse_header(header: &str) -> Option<(String, String)> {
+    let (name, value) = header.split_once(':')?;
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some((name.to_string(), value.trim().to_string()))
+    }
+}
+
+fn expand_env_vars(value: &str) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let variable = &rest[start + 2..];
+        match variable.find('}') {
+            Some(end) => {
+                let name = &variable[..end];
+                match env::var(name) {
+                    Ok(variable_value) => expanded.push_str(&variable_value),
+                    Err(_) => return Err(format!("Environment variable {} is not set", name))
+                }
+                rest = &variable[end + 1..];
+            }
+            None => return Err(format!("Missing closing brace in \"{}\"", value))
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
 }
 
 fn exit_with_error(message: &str) -> ! {
     eprintln!("ERROR: {}", message);
     process::exit(1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_environment_variables() {
+        env::set_var("GRAPHQL_FREEZE_TEST_TOKEN", "secret");
+        assert_eq!(expand_env_vars("Bearer ${GRAPHQL_FREEZE_TEST_TOKEN}").unwrap(), "Bearer secret");
+        assert_eq!(expand_env_vars("${GRAPHQL_FREEZE_TEST_TOKEN}-${GRAPHQL_FREEZE_TEST_TOKEN}").unwrap(), "secret-secret");
+        assert_eq!(expand_env_vars("no variables, $HOME {}").unwrap(), "no variables, $HOME {}");
+    }
+
+    #[test]
+    fn rejects_missing_environment_variables() {
+        assert!(expand_env_vars("${GRAPHQL_FREEZE_TEST_UNSET_VARIABLE}").is_err());
+        assert!(expand_env_vars("Bearer ${GRAPHQL_FREEZE_TEST_TOKEN").is_err());
+    }
+
+    #[test]
+    fn cli_headers_replace_profile_headers() {
+        let profile = HashMap::from([
+            ("Authorization".to_string(), "Bearer profile".to_string()),
+            ("X-Client".to_string(), "freeze".to_string())
+        ]);
+        let cli = vec![
+            ("authorization".to_string(), "Bearer cli".to_string()),
+            ("Accept".to_string(), "a".to_string()),
+            ("Accept".to_string(), "b".to_string())
+        ];
+        let mut headers = profile_headers(&Some(profile), cli, "default");
+        headers.sort();
+        let expected = [("Accept", "a"), ("Accept", "b"), ("X-Client", "freeze"), ("authorization", "Bearer cli")];
+        let expected: Vec<(String, String)> = expected.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        assert_eq!(headers, expected);
+    }
+
+    #[test]
+    fn accepts_only_one_schema_source_flag() {
+        assert!(Cli::try_parse_from(["codegen", "--introspection-file", "schema.json"]).is_ok());
+        let conflicting = [
+            ["codegen", "--url", "http://localhost/graphql", "--file", "schema.graphql"],
+            ["codegen", "--url", "http://localhost/graphql", "--introspection-file", "schema.json"],
+            ["codegen", "--file", "schema.graphql", "--introspection-file", "schema.json"]
+        ];
+        for args in conflicting {
+            assert!(Cli::try_parse_from(args).is_err());
+        }
+    }
+}
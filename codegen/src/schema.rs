@@ -1,24 +1,63 @@
-use std::collections::BTreeSet;
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use crate::diagnostics::{self, Diagnostic};
 
 pub struct Object {
     pub name: String,
-    pub fields: Vec<Field>
+    pub description: Option<String>,
+    pub fields: Vec<Field>,
+    /// Input object marked with `@oneOf`, exactly one field must be given.
+    pub one_of: bool
 }
 
 pub struct Field {
     pub name: String,
+    pub description: Option<String>,
+    pub deprecated: Option<String>,
+    pub default_value: Option<String>,
     pub field_type: GqlType
 }
 
 pub struct Argument {
     pub name: String,
+    pub description: Option<String>,
+    pub deprecated: Option<String>,
+    pub default_value: Option<String>,
     pub argument_type: GqlType,
     pub type_name: String
 }
 
+pub struct Interface {
+    pub name: String,
+    pub description: Option<String>,
+    pub fields: Vec<Field>,
+    /// Interfaces this interface implements.
+    pub interfaces: Vec<String>,
+    pub implementors: Vec<String>
+}
+
+pub struct Scalar {
+    pub description: Option<String>,
+    /// Url given with `@specifiedBy`.
+    pub specified_by_url: Option<String>
+}
+
+pub struct Union {
+    pub name: String,
+    pub description: Option<String>,
+    pub members: Vec<String>
+}
+
 pub struct Enum {
     pub name: String,
-    pub values: Vec<String>
+    pub description: Option<String>,
+    pub values: Vec<EnumValue>
+}
+
+pub struct EnumValue {
+    pub name: String,
+    pub description: Option<String>,
+    pub deprecated: Option<String>
 }
 
 pub enum GqlType {
@@ -33,11 +72,327 @@ pub enum GqlType {
     }
 }
 
+pub struct SchemaSource {
+    pub name: Option<String>,
+    pub content: String
+}
+
+pub struct RootTypes {
+    pub query: Option<String>,
+    pub mutation: Option<String>,
+    pub subscription: Option<String>
+}
+
 pub struct GqlDocument {
     pub inputs: Vec<Object>,
     pub outputs: Vec<Object>,
+    pub interfaces: Vec<Interface>,
+    pub unions: Vec<Union>,
     pub enums: Vec<Enum>,
-    pub scalars: BTreeSet<String>
+    pub scalars: BTreeMap<String, Scalar>,
+    pub root_types: RootTypes
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum SortOrder {
+    #[serde(rename = "alphabetical")]
+    Alphabetical,
+    #[serde(rename = "declaration")]
+    Declaration
+}
+
+impl GqlDocument {
+    pub fn sort(&mut self, type_order: SortOrder, field_order: SortOrder) {
+        if let SortOrder::Alphabetical = type_order {
+            self.inputs.sort_by(|a, b| a.name.cmp(&b.name));
+            self.outputs.sort_by(|a, b| a.name.cmp(&b.name));
+            self.interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+            self.unions.sort_by(|a, b| a.name.cmp(&b.name));
+            self.enums.sort_by(|a, b| a.name.cmp(&b.name));
+            for interface in &mut self.interfaces {
+                interface.interfaces.sort();
+                interface.implementors.sort();
+            }
+            for union in &mut self.unions {
+                union.members.sort();
+            }
+        }
+        if let SortOrder::Alphabetical = field_order {
+            for object in self.inputs.iter_mut().chain(self.outputs.iter_mut()) {
+                sort_fields(&mut object.fields);
+            }
+            for interface in &mut self.interfaces {
+                sort_fields(&mut interface.fields);
+            }
+            for enum_def in &mut self.enums {
+                enum_def.values.sort_by(|a, b| a.name.cmp(&b.name));
+            }
+        }
+    }
+
+    pub fn remove_introspection_types(&mut self) {
+        self.inputs.retain(|object| !is_introspection_name(&object.name));
+        self.outputs.retain(|object| !is_introspection_name(&object.name));
+        self.interfaces.retain(|interface| !is_introspection_name(&interface.name));
+        self.unions.retain(|union| !is_introspection_name(&union.name));
+        self.enums.retain(|enum_def| !is_introspection_name(&enum_def.name));
+        for object in &mut self.outputs {
+            object.fields.retain(|field| !is_introspection_name(&field.name));
+        }
+    }
+
+    pub fn remove_deprecated(&mut self) {
+        for object in self.inputs.iter_mut().chain(self.outputs.iter_mut()) {
+            remove_deprecated_fields(&mut object.fields);
+        }
+        for interface in &mut self.interfaces {
+            remove_deprecated_fields(&mut interface.fields);
+        }
+        for enum_def in &mut self.enums {
+            enum_def.values.retain(|value| value.deprecated.is_none());
+        }
+    }
+
+    /// Checks that every referenced type is defined and that arguments and
+    /// input fields are plain input types, reporting all problems at once.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        for object in &self.outputs {
+            self.validate_output_fields(&object.name, &object.fields, &mut diagnostics);
+        }
+        for interface in &self.interfaces {
+            self.validate_output_fields(&interface.name, &interface.fields, &mut diagnostics);
+            let path = format!("interfaces of {}", interface.name);
+            for name in &interface.interfaces {
+                self.validate_reference(name, ReferenceKind::Interface, &path, &mut diagnostics);
+            }
+            let path = format!("implementations of {}", interface.name);
+            for name in &interface.implementors {
+                self.validate_reference(name, ReferenceKind::Object, &path, &mut diagnostics);
+            }
+        }
+        for union in &self.unions {
+            let path = format!("union {}", union.name);
+            for name in &union.members {
+                self.validate_reference(name, ReferenceKind::Object, &path, &mut diagnostics);
+            }
+        }
+        for input in &self.inputs {
+            for field in &input.fields {
+                let path = format!("{}.{}", input.name, field.name);
+                self.validate_type(&field.field_type, &path, &mut diagnostics);
+                let is_nullable = matches!(field.field_type, GqlType::Nullable(_));
+                if input.one_of && (!is_nullable || field.default_value.is_some()) {
+                    let message = format!("{} must be nullable and have no default value, {} is a oneOf input", path, input.name);
+                    diagnostics.push(Diagnostic::error(diagnostics::INVALID_ONE_OF_FIELD, message));
+                }
+            }
+        }
+        diagnostics
+    }
+
+    fn validate_output_fields(&self, type_name: &str, fields: &[Field], diagnostics: &mut Vec<Diagnostic>) {
+        for field in fields {
+            let path = format!("{}.{}", type_name, field.name);
+            match &field.field_type {
+                GqlType::Function { inputs, output } => {
+                    for input in inputs {
+                        let path = format!("{}({})", path, input.name);
+                        self.validate_type(&input.argument_type, &path, diagnostics);
+                    }
+                    self.validate_type(output, &path, diagnostics);
+                }
+                field_type => self.validate_type(field_type, &path, diagnostics)
+            }
+        }
+    }
+
+    fn validate_type(&self, gql_type: &GqlType, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let (name, defined) = match gql_type {
+            GqlType::List(inner) | GqlType::Nullable(inner) => return self.validate_type(inner, path, diagnostics),
+            GqlType::Function { .. } => {
+                let message = format!("{} takes arguments in a position where only plain types are allowed", path);
+                diagnostics.push(Diagnostic::error(diagnostics::INVALID_INPUT_TYPE, message));
+                return;
+            }
+            GqlType::Scalar(name) => (name, self.scalars.contains_key(name)),
+            GqlType::Enum(name) => (name, self.enums.iter().any(|enum_def| &enum_def.name == name)),
+            GqlType::Object(name) => (name, self.is_object_type(name))
+        };
+        if !defined {
+            let message = format!("Unknown type `{}` in {}", name, path);
+            diagnostics.push(Diagnostic::error(diagnostics::UNKNOWN_TYPE, message));
+        }
+    }
+
+    fn validate_reference(&self, name: &str, kind: ReferenceKind, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let matches = match kind {
+            ReferenceKind::Object => self.outputs.iter().any(|object| object.name == name),
+            ReferenceKind::Interface => self.interfaces.iter().any(|interface| interface.name == name)
+        };
+        if !matches {
+            let is_defined = self.is_object_type(name)
+                || self.scalars.contains_key(name)
+                || self.enums.iter().any(|enum_def| enum_def.name == name);
+            diagnostics.push(invalid_reference(name, path, kind, is_defined));
+        }
+    }
+
+    fn is_object_type(&self, name: &str) -> bool {
+        self.inputs.iter().any(|object| object.name == name)
+            || self.outputs.iter().any(|object| object.name == name)
+            || self.interfaces.iter().any(|interface| interface.name == name)
+            || self.unions.iter().any(|union| union.name == name)
+    }
+}
+
+/// Positions that accept only one kind of type, union members and the
+/// targets of `implements`.
+#[derive(Clone, Copy)]
+pub enum ReferenceKind {
+    Object,
+    Interface
+}
+
+/// Reports `name` in `path`, which is either not defined or not of the kind
+/// the position requires.
+pub fn invalid_reference(name: &str, path: &str, kind: ReferenceKind, is_defined: bool) -> Diagnostic {
+    if !is_defined {
+        let message = format!("Unknown type `{}` in {}", name, path);
+        return Diagnostic::error(diagnostics::UNKNOWN_TYPE, message);
+    }
+    let expected = match kind {
+        ReferenceKind::Object => "an object type",
+        ReferenceKind::Interface => "an interface"
+    };
+    let message = format!("`{}` in {} is not {}", name, path, expected);
+    Diagnostic::error(diagnostics::INVALID_TYPE_REFERENCE, message)
+}
+
+fn sort_fields(fields: &mut [Field]) {
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    for field in fields {
+        if let GqlType::Function { inputs, .. } = &mut field.field_type {
+            inputs.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
 }
 
+fn remove_deprecated_fields(fields: &mut Vec<Field>) {
+    fields.retain(|field| field.deprecated.is_none());
+    for field in fields {
+        if let GqlType::Function { inputs, .. } = &mut field.field_type {
+            inputs.retain(|input| input.deprecated.is_none());
+        }
+    }
+}
+
+pub fn is_introspection_name(name: &str) -> bool {
+    name.starts_with(INTROSPECTION_PREFIX)
+}
+
+pub const INTROSPECTION_PREFIX: &str = "__";
+
+pub const DEFAULT_DEPRECATION_REASON: &str = "No longer supported";
+
+pub const DEFAULT_QUERY_TYPE: &str = "Query";
+pub const DEFAULT_MUTATION_TYPE: &str = "Mutation";
+pub const DEFAULT_SUBSCRIPTION_TYPE: &str = "Subscription";
+
 pub const BUILT_IN_SCALARS: &[&str] = &[ "Int", "String", "Float", "Boolean", "ID" ];
+
+pub const BUILT_IN_DIRECTIVES: &[&str] = &[ "skip", "include", "deprecated", "specifiedBy", "oneOf", "defer", "stream" ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema_sdl;
+
+    const SDL: &str = "
+        type Query { search(term: String, limit: Int, after: String): [Result!]! node: Node }
+        union Result = User | Post
+        interface Resource implements Node & Entity { name: String id: ID! }
+        interface Node { id: ID! }
+        interface Entity { name: String }
+        type User implements Node & Entity { name: String id: ID! }
+        type Post { b: Int a: Int }
+        enum Role { USER ADMIN }
+        input Filter { term: String limit: Int }
+    ";
+
+    fn document() -> GqlDocument {
+        let sources = [SchemaSource { name: None, content: SDL.to_string() }];
+        schema_sdl::from_sdl_sources(&sources).ok().unwrap().0
+    }
+
+    fn names<T>(items: &[T], name: impl Fn(&T) -> &str) -> Vec<String> {
+        items.iter().map(|item| name(item).to_string()).collect()
+    }
+
+    fn argument_names(field: &Field) -> Vec<String> {
+        match &field.field_type {
+            GqlType::Function { inputs, .. } => names(inputs, |argument| &argument.name),
+            _ => Vec::new()
+        }
+    }
+
+    #[test]
+    fn sorts_types_and_fields_alphabetically() {
+        let mut document = document();
+        document.sort(SortOrder::Alphabetical, SortOrder::Alphabetical);
+        assert_eq!(names(&document.outputs, |object| &object.name), ["Post", "Query", "User"]);
+        assert_eq!(names(&document.interfaces, |interface| &interface.name), ["Entity", "Node", "Resource"]);
+        assert_eq!(document.interfaces[2].interfaces, ["Entity", "Node"]);
+        assert_eq!(document.interfaces[1].implementors, ["User"]);
+        assert_eq!(document.unions[0].members, ["Post", "User"]);
+        assert_eq!(names(&document.outputs[0].fields, |field| &field.name), ["a", "b"]);
+        assert_eq!(names(&document.inputs[0].fields, |field| &field.name), ["limit", "term"]);
+        assert_eq!(names(&document.enums[0].values, |value| &value.name), ["ADMIN", "USER"]);
+        let query = &document.outputs[1];
+        assert_eq!(names(&query.fields, |field| &field.name), ["node", "search"]);
+        assert_eq!(argument_names(&query.fields[1]), ["after", "limit", "term"]);
+    }
+
+    #[test]
+    fn keeps_declaration_order() {
+        let mut document = document();
+        document.sort(SortOrder::Declaration, SortOrder::Declaration);
+        assert_eq!(names(&document.outputs, |object| &object.name), ["Query", "User", "Post"]);
+        assert_eq!(names(&document.interfaces, |interface| &interface.name), ["Resource", "Node", "Entity"]);
+        assert_eq!(document.interfaces[0].interfaces, ["Node", "Entity"]);
+        assert_eq!(document.unions[0].members, ["User", "Post"]);
+        assert_eq!(names(&document.enums[0].values, |value| &value.name), ["USER", "ADMIN"]);
+        let query = &document.outputs[0];
+        assert_eq!(names(&query.fields, |field| &field.name), ["search", "node"]);
+        assert_eq!(argument_names(&query.fields[0]), ["term", "limit", "after"]);
+    }
+
+    #[test]
+    fn validate_reports_every_invalid_reference() {
+        let sources = [SchemaSource { name: None, content: "type Query { a: A }\ntype A { id: ID }\ninterface Node { id: ID }\nunion U = A".to_string() }];
+        let (mut document, _) = schema_sdl::from_sdl_sources(&sources).ok().unwrap();
+        assert!(document.validate().is_empty());
+        document.outputs[0].fields.push(Field {
+            name: "created".to_string(),
+            description: None,
+            deprecated: None,
+            default_value: None,
+            field_type: GqlType::Nullable(Box::new(GqlType::Scalar("Date".to_string())))
+        });
+        document.unions[0].members.extend(["Missing".to_string(), "Node".to_string()]);
+        document.interfaces[0].interfaces.extend(["Ghost".to_string(), "A".to_string()]);
+        document.interfaces[0].implementors.push("Node".to_string());
+        let problems: Vec<String> = document.validate()
+            .iter()
+            .map(|diagnostic| format!("{} {}", diagnostic.code, diagnostic.message))
+            .collect();
+        assert_eq!(problems, [
+            "E0002 Unknown type `Date` in Query.created",
+            "E0002 Unknown type `Ghost` in interfaces of Node",
+            "E0007 `A` in interfaces of Node is not an interface",
+            "E0007 `Node` in implementations of Node is not an object type",
+            "E0002 Unknown type `Missing` in union U",
+            "E0007 `Node` in union U is not an object type"
+        ]);
+    }
+}
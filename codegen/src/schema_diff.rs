@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::schema;
+use crate::schema::{Argument, Enum, Field, GqlDocument, GqlType, Interface, Object, RootTypes, Scalar, Union};
+use crate::schema_printer::type_to_sdl;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    /// Existing requests or generated code may no longer work.
+    Breaking,
+    /// Existing requests keep working but clients may behave differently,
+    /// such as a new enum value reaching an exhaustive switch.
+    Dangerous,
+    Safe
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Breaking => write!(f, "breaking"),
+            ChangeKind::Dangerous => write!(f, "dangerous"),
+            ChangeKind::Safe => write!(f, "safe")
+        }
+    }
+}
+
+pub struct Change {
+    pub kind: ChangeKind,
+    pub message: String
+}
+
+enum TypeDefinition<'a> {
+    Object(&'a Object),
+    Input(&'a Object),
+    Interface(&'a Interface),
+    Union(&'a Union),
+    Enum(&'a Enum),
+    Scalar(&'a Scalar)
+}
+
+impl TypeDefinition<'_> {
+    fn kind_name(&self) -> &'static str {
+        match self {
+            TypeDefinition::Object(_) => "object",
+            TypeDefinition::Input(_) => "input object",
+            TypeDefinition::Interface(_) => "interface",
+            TypeDefinition::Union(_) => "union",
+            TypeDefinition::Enum(_) => "enum",
+            TypeDefinition::Scalar(_) => "scalar"
+        }
+    }
+}
+
+/// Lists what changed from `old` to `new`, in the type order of the documents
+/// with removed and changed types before added ones.
+pub fn diff(old: &GqlDocument, new: &GqlDocument) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+    diff_root_types(&old.root_types, &new.root_types, &mut changes);
+    let old_types = named_types(old);
+    let new_types = named_types(new);
+    let new_lookup: HashMap<&str, &TypeDefinition> = new_types.iter().map(|(name, definition)| (*name, definition)).collect();
+    let old_lookup: HashMap<&str, &TypeDefinition> = old_types.iter().map(|(name, definition)| (*name, definition)).collect();
+    for (name, old_definition) in &old_types {
+        match new_lookup.get(name) {
+            Some(new_definition) => diff_type(name, old_definition, new_definition, &mut changes),
+            None => changes.push(breaking(format!("Type `{}` was removed", name)))
+        }
+    }
+    for (name, new_definition) in &new_types {
+        if !old_lookup.contains_key(name) {
+            changes.push(safe(format!("{} `{}` was added", capitalize(new_definition.kind_name()), name)));
+        }
+    }
+    changes
+}
+
+fn named_types(document: &GqlDocument) -> Vec<(&str, TypeDefinition<'_>)> {
+    let mut types: Vec<(&str, TypeDefinition)> = Vec::new();
+    for (name, scalar) in document.scalars.iter().filter(|(name, _)| !schema::BUILT_IN_SCALARS.contains(&name.as_str())) {
+        types.push((name, TypeDefinition::Scalar(scalar)));
+    }
+    for enum_def in &document.enums {
+        types.push((&enum_def.name, TypeDefinition::Enum(enum_def)));
+    }
+    for interface in &document.interfaces {
+        types.push((&interface.name, TypeDefinition::Interface(interface)));
+    }
+    for output in &document.outputs {
+        types.push((&output.name, TypeDefinition::Object(output)));
+    }
+    for union in &document.unions {
+        types.push((&union.name, TypeDefinition::Union(union)));
+    }
+    for input in &document.inputs {
+        types.push((&input.name, TypeDefinition::Input(input)));
+    }
+    types
+}
+
+fn diff_root_types(old: &RootTypes, new: &RootTypes, changes: &mut Vec<Change>) {
+    let operations = [
+        ("query", &old.query, &new.query),
+        ("mutation", &old.mutation, &new.mutation),
+        ("subscription", &old.subscription, &new.subscription)
+    ];
+    for (operation, old_type, new_type) in operations {
+        match (old_type, new_type) {
+            (Some(old_type), Some(new_type)) if old_type != new_type => {
+                changes.push(breaking(format!("Root {} type changed from `{}` to `{}`", operation, old_type, new_type)));
+            }
+            (Some(old_type), None) => changes.push(breaking(format!("Root {} type `{}` was removed", operation, old_type))),
+            (None, Some(new_type)) => changes.push(safe(format!("Root {} type `{}` was added", operation, new_type))),
+            _ => ()
+        }
+    }
+}
+
+fn diff_type(name: &str, old: &TypeDefinition, new: &TypeDefinition, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (TypeDefinition::Object(old), TypeDefinition::Object(new)) => diff_output_fields(name, &old.fields, &new.fields, changes),
+        (TypeDefinition::Interface(old), TypeDefinition::Interface(new)) => {
+            diff_output_fields(name, &old.fields, &new.fields, changes);
+            diff_members(
+                &old.interfaces,
+                &new.interfaces,
+                |member| format!("`{}` no longer implements interface `{}`", name, member),
+                |member| format!("`{}` now implements interface `{}`", name, member),
+                changes
+            );
+            diff_members(
+                &old.implementors,
+                &new.implementors,
+                |member| format!("`{}` no longer implements interface `{}`", member, name),
+                |member| format!("`{}` now implements interface `{}`", member, name),
+                changes
+            );
+        }
+        (TypeDefinition::Union(old), TypeDefinition::Union(new)) => {
+            diff_members(
+                &old.members,
+                &new.members,
+                |member| format!("`{}` was removed from union `{}`", member, name),
+                |member| format!("`{}` was added to union `{}`", member, name),
+                changes
+            );
+        }
+        (TypeDefinition::Enum(old), TypeDefinition::Enum(new)) => diff_enum(name, old, new, changes),
+        (TypeDefinition::Input(old), TypeDefinition::Input(new)) => diff_input(name, old, new, changes),
+        (TypeDefinition::Scalar(old), TypeDefinition::Scalar(new)) => {
+            if old.specified_by_url != new.specified_by_url {
+                let message = format!(
+                    "Scalar `{}` changed specifiedBy url from {} to {}",
+                    name,
+                    describe_value(&old.specified_by_url),
+                    describe_value(&new.specified_by_url)
+                );
+                changes.push(dangerous(message));
+            }
+        }
+        (old, new) => {
+            changes.push(breaking(format!("`{}` changed from {} to {}", name, old.kind_name(), new.kind_name())));
+        }
+    }
+}
+
+fn diff_members(
+    old: &[String],
+    new: &[String],
+    removed_message: impl Fn(&str) -> String,
+    added_message: impl Fn(&str) -> String,
+    changes: &mut Vec<Change>
+) {
+    for member in old.iter().filter(|member| !new.contains(member)) {
+        changes.push(breaking(removed_message(member)));
+    }
+    for member in new.iter().filter(|member| !old.contains(member)) {
+        changes.push(dangerous(added_message(member)));
+    }
+}
+
+fn diff_enum(name: &str, old: &Enum, new: &Enum, changes: &mut Vec<Change>) {
+    for old_value in &old.values {
+        let path = format!("{}.{}", name, old_value.name);
+        match new.values.iter().find(|value| value.name == old_value.name) {
+            Some(new_value) => diff_deprecation(&path, &old_value.deprecated, &new_value.deprecated, changes),
+            None => changes.push(breaking(format!("Enum value `{}` was removed", path)))
+        }
+    }
+    for new_value in new.values.iter().filter(|value| !old.values.iter().any(|old_value| old_value.name == value.name)) {
+        changes.push(dangerous(format!("Enum value `{}.{}` was added", name, new_value.name)));
+    }
+}
+
+fn diff_output_fields(type_name: &str, old: &[Field], new: &[Field], changes: &mut Vec<Change>) {
+    for old_field in old {
+        let path = format!("{}.{}", type_name, old_field.name);
+        let new_field = match new.iter().find(|field| field.name == old_field.name) {
+            Some(new_field) => new_field,
+            None => {
+                changes.push(breaking(format!("Field `{}` was removed", path)));
+                continue;
+            }
+        };
+        let (old_arguments, old_type) = split_function(&old_field.field_type);
+        let (new_arguments, new_type) = split_function(&new_field.field_type);
+        let message = format!("Field `{}` changed type from `{}` to `{}`", path, type_to_sdl(old_type), type_to_sdl(new_type));
+        if !is_compatible(old_type, new_type, Position::Output) {
+            changes.push(breaking(message));
+        } else if type_to_sdl(old_type) != type_to_sdl(new_type) {
+            changes.push(safe(message));
+        }
+        diff_arguments(&path, old_arguments, new_arguments, changes);
+        diff_deprecation(&path, &old_field.deprecated, &new_field.deprecated, changes);
+    }
+    for new_field in new.iter().filter(|field| !old.iter().any(|old_field| old_field.name == field.name)) {
+        changes.push(safe(format!("Field `{}.{}` was added", type_name, new_field.name)));
+    }
+}
+
+fn split_function(field_type: &GqlType) -> (&[Argument], &GqlType) {
+    match field_type {
+        GqlType::Function { inputs, output } => (inputs, output),
+        field_type => (&[], field_type)
+    }
+}
+
+fn diff_arguments(field_path: &str, old: &[Argument], new: &[Argument], changes: &mut Vec<Change>) {
+    for old_argument in old {
+        let path = format!("{}({})", field_path, old_argument.name);
+        let new_argument = match new.iter().find(|argument| argument.name == old_argument.name) {
+            Some(new_argument) => new_argument,
+            None => {
+                changes.push(breaking(format!("Argument `{}` was removed", path)));
+                continue;
+            }
+        };
+        diff_input_value(
+            &format!("Argument `{}`", path),
+            (&old_argument.argument_type, &old_argument.default_value),
+            (&new_argument.argument_type, &new_argument.default_value),
+            changes
+        );
+        diff_deprecation(&path, &old_argument.deprecated, &new_argument.deprecated, changes);
+    }
+    for new_argument in new.iter().filter(|argument| !old.iter().any(|old_argument| old_argument.name == argument.name)) {
+        let subject = format!("Argument `{}({})`", field_path, new_argument.name);
+        diff_added_input_value(&subject, &new_argument.argument_type, &new_argument.default_value, changes);
+    }
+}
+
+fn diff_input(name: &str, old: &Object, new: &Object, changes: &mut Vec<Change>) {
+    match (old.one_of, new.one_of) {
+        (false, true) => changes.push(breaking(format!("Input object `{}` is now oneOf", name))),
+        (true, false) => changes.push(dangerous(format!("Input object `{}` is no longer oneOf", name))),
+        _ => ()
+    }
+    for old_field in &old.fields {
+        let path = format!("{}.{}", name, old_field.name);
+        let new_field = match new.fields.iter().find(|field| field.name == old_field.name) {
+            Some(new_field) => new_field,
+            None => {
+                changes.push(breaking(format!("Input field `{}` was removed", path)));
+                continue;
+            }
+        };
+        diff_input_value(
+            &format!("Input field `{}`", path),
+            (&old_field.field_type, &old_field.default_value),
+            (&new_field.field_type, &new_field.default_value),
+            changes
+        );
+        diff_deprecation(&path, &old_field.deprecated, &new_field.deprecated, changes);
+    }
+    for new_field in new.fields.iter().filter(|field| !old.fields.iter().any(|old_field| old_field.name == field.name)) {
+        let subject = format!("Input field `{}.{}`", name, new_field.name);
+        diff_added_input_value(&subject, &new_field.field_type, &new_field.default_value, changes);
+    }
+}
+
+fn diff_input_value(
+    subject: &str,
+    (old_type, old_default): (&GqlType, &Option<String>),
+    (new_type, new_default): (&GqlType, &Option<String>),
+    changes: &mut Vec<Change>
+) {
+    let message = format!("{} changed type from `{}` to `{}`", subject, type_to_sdl(old_type), type_to_sdl(new_type));
+    if !is_compatible(old_type, new_type, Position::Input) {
+        changes.push(breaking(message));
+    } else if type_to_sdl(old_type) != type_to_sdl(new_type) {
+        changes.push(safe(message));
+    }
+    if old_default != new_default {
+        let message = format!("{} changed default value from {} to {}", subject, describe_value(old_default), describe_value(new_default));
+        changes.push(dangerous(message));
+    }
+}
+
+fn describe_value(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("`{}`", value),
+        None => "none".to_string()
+    }
+}
+
+fn diff_added_input_value(subject: &str, value_type: &GqlType, default_value: &Option<String>, changes: &mut Vec<Change>) {
+    let is_required = !matches!(value_type, GqlType::Nullable(_)) && default_value.is_none();
+    if is_required {
+        changes.push(breaking(format!("Required {} was added", lowercase_first(subject))));
+    } else {
+        changes.push(dangerous(format!("Optional {} was added", lowercase_first(subject))));
+    }
+}
+
+fn diff_deprecation(path: &str, old: &Option<String>, new: &Option<String>, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (None, Some(reason)) => changes.push(safe(format!("`{}` was deprecated: {}", path, reason))),
+        (Some(_), None) => changes.push(safe(format!("`{}` is no longer deprecated", path))),
+        _ => ()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Position {
+    Input,
+    Output
+}
+
+/// Outputs may become stricter (nullable to non-null) and inputs may become
+/// looser (non-null to nullable) without breaking existing requests.
+fn is_compatible(old: &GqlType, new: &GqlType, position: Position) -> bool {
+    match (old, new, position) {
+        (GqlType::Nullable(old), GqlType::Nullable(new), _) => is_compatible(old, new, position),
+        (GqlType::Nullable(old), new, Position::Output) => is_compatible(old, new, position),
+        (old, GqlType::Nullable(new), Position::Input) => is_compatible(old, new, position),
+        (GqlType::Nullable(_), _, Position::Input) | (_, GqlType::Nullable(_), Position::Output) => false,
+        (GqlType::List(old), GqlType::List(new), _) => is_compatible(old, new, position),
+        (GqlType::List(_), _, _) | (_, GqlType::List(_), _) => false,
+        (old, new, _) => type_to_sdl(old) == type_to_sdl(new)
+    }
+}
+
+fn breaking(message: String) -> Change {
+    Change { kind: ChangeKind::Breaking, message }
+}
+
+fn dangerous(message: String) -> Change {
+    Change { kind: ChangeKind::Dangerous, message }
+}
+
+fn safe(message: String) -> Change {
+    Change { kind: ChangeKind::Safe, message }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new()
+    }
+}
+
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new()
+    }
+}
+
+/// Prints changes to stdout, most severe first, followed by a count per kind.
+pub fn print(changes: &[Change]) {
+    let kinds = [ChangeKind::Breaking, ChangeKind::Dangerous, ChangeKind::Safe];
+    for kind in kinds {
+        for change in changes.iter().filter(|change| change.kind == kind) {
+            println!("{}: {}", change.kind, change.message);
+        }
+    }
+    if changes.is_empty() {
+        println!("No changes");
+    } else {
+        let count = |kind: ChangeKind| changes.iter().filter(|change| change.kind == kind).count();
+        println!(
+            "found {} breaking, {} dangerous and {} safe change(s)",
+            count(ChangeKind::Breaking),
+            count(ChangeKind::Dangerous),
+            count(ChangeKind::Safe)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SchemaSource;
+    use crate::schema_sdl;
+
+    fn document(sdl: &str) -> GqlDocument {
+        let sources = [SchemaSource { name: None, content: sdl.to_string() }];
+        schema_sdl::from_sdl_sources(&sources).ok().unwrap().0
+    }
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        diff(&document(old), &document(new))
+            .iter()
+            .map(|change| format!("{}: {}", change.kind, change.message))
+            .collect()
+    }
+
+    fn assert_changes(cases: &[(&str, &str, &[&str])]) {
+        for (old, new, expected) in cases {
+            assert_eq!(changes(old, new), *expected, "changes from `{}` to `{}`", old, new);
+        }
+    }
+
+    #[test]
+    fn reports_no_changes_for_equal_schemas() {
+        let sdl = "type Query { user(id: ID!): User }\ntype User { id: ID! }";
+        assert!(changes(sdl, sdl).is_empty());
+    }
+
+    #[test]
+    fn classifies_breaking_changes() {
+        assert_changes(&[
+            ("type Query { a: A }\ntype A { id: ID }", "type Query { a: ID }", &["breaking: Field `Query.a` changed type from `A` to `ID`", "breaking: Type `A` was removed"]),
+            ("type Query { a: A }\ntype A { id: ID }", "type Query { a: A }\ninput A { id: ID }", &["breaking: `A` changed from object to input object"]),
+            ("type Query { a: Int }\ntype Root { a: Int }", "schema { query: Root }\ntype Query { a: Int }\ntype Root { a: Int }", &["breaking: Root query type changed from `Query` to `Root`"]),
+            ("type Query { a: Int }\ntype Mutation { a: Int }", "type Query { a: Int }", &["breaking: Root mutation type `Mutation` was removed", "breaking: Type `Mutation` was removed"]),
+            ("type Query { a: Int, b: Int }", "type Query { a: Int }", &["breaking: Field `Query.b` was removed"]),
+            ("type Query { a: Int! }", "type Query { a: Int }", &["breaking: Field `Query.a` changed type from `Int!` to `Int`"]),
+            ("type Query { a: [Int] }", "type Query { a: Int }", &["breaking: Field `Query.a` changed type from `[Int]` to `Int`"]),
+            ("type Query { a(n: Int): Int }", "type Query { a: Int }", &["breaking: Argument `Query.a(n)` was removed"]),
+            ("type Query { a(n: Int): Int }", "type Query { a(n: Int!): Int }", &["breaking: Argument `Query.a(n)` changed type from `Int` to `Int!`"]),
+            ("type Query { a: Int }", "type Query { a(n: Int!): Int }", &["breaking: Required argument `Query.a(n)` was added"]),
+            ("type Query { a(f: F): Int }\ninput F { x: Int }", "type Query { a(f: F): Int }\ninput F { x: Int, y: Int! }", &["breaking: Required input field `F.y` was added"]),
+            ("type Query { a(f: F): Int }\ninput F { x: Int, y: Int }", "type Query { a(f: F): Int }\ninput F { x: Int }", &["breaking: Input field `F.y` was removed"]),
+            ("type Query { a(f: F): Int }\ninput F { x: Int }", "type Query { a(f: F): Int }\ninput F @oneOf { x: Int }", &["breaking: Input object `F` is now oneOf"]),
+            ("type Query { e: E }\nenum E { A B }", "type Query { e: E }\nenum E { A }", &["breaking: Enum value `E.B` was removed"]),
+            ("type Query { u: U }\nunion U = A | B\ntype A { a: Int }\ntype B { b: Int }", "type Query { u: U }\nunion U = A\ntype A { a: Int }\ntype B { b: Int }", &["breaking: `B` was removed from union `U`"]),
+            ("type Query { n: N }\ninterface N { id: ID }\ntype A implements N { id: ID }", "type Query { n: N }\ninterface N { id: ID }\ntype A { id: ID }", &["breaking: `A` no longer implements interface `N`"]),
+            ("type Query { n: N }\ninterface N { id: ID }\ninterface E implements N { id: ID }", "type Query { n: N }\ninterface N { id: ID }\ninterface E { id: ID }", &["breaking: `E` no longer implements interface `N`"])
+        ]);
+    }
+
+    #[test]
+    fn classifies_dangerous_changes() {
+        assert_changes(&[
+            ("type Query { e: E }\nenum E { A }", "type Query { e: E }\nenum E { A B }", &["dangerous: Enum value `E.B` was added"]),
+            ("type Query { u: U }\nunion U = A\ntype A { a: Int }\ntype B { b: Int }", "type Query { u: U }\nunion U = A | B\ntype A { a: Int }\ntype B { b: Int }", &["dangerous: `B` was added to union `U`"]),
+            ("type Query { n: N }\ninterface N { id: ID }\ntype A { id: ID }", "type Query { n: N }\ninterface N { id: ID }\ntype A implements N { id: ID }", &["dangerous: `A` now implements interface `N`"]),
+            ("type Query { a(n: Int = 1): Int }", "type Query { a(n: Int = 2): Int }", &["dangerous: Argument `Query.a(n)` changed default value from `1` to `2`"]),
+            ("type Query { a(n: Int): Int }", "type Query { a(n: Int = 2): Int }", &["dangerous: Argument `Query.a(n)` changed default value from none to `2`"]),
+            ("type Query { n: N }\ninterface N { id: ID }\ninterface E { id: ID }", "type Query { n: N }\ninterface N { id: ID }\ninterface E implements N { id: ID }", &["dangerous: `E` now implements interface `N`"]),
+            ("type Query { d: Date }\nscalar Date", "type Query { d: Date }\nscalar Date @specifiedBy(url: \"https://example.com/date\")", &["dangerous: Scalar `Date` changed specifiedBy url from none to `https://example.com/date`"]),
+            ("type Query { a: Int }", "type Query { a(n: Int): Int }", &["dangerous: Optional argument `Query.a(n)` was added"]),
+            ("type Query { a: Int }", "type Query { a(n: Int! = 1): Int }", &["dangerous: Optional argument `Query.a(n)` was added"]),
+            ("type Query { a(f: F): Int }\ninput F { x: Int }", "type Query { a(f: F): Int }\ninput F { x: Int, y: Int }", &["dangerous: Optional input field `F.y` was added"]),
+            ("type Query { a(f: F): Int }\ninput F @oneOf { x: Int }", "type Query { a(f: F): Int }\ninput F { x: Int }", &["dangerous: Input object `F` is no longer oneOf"])
+        ]);
+    }
+
+    #[test]
+    fn classifies_safe_changes() {
+        assert_changes(&[
+            ("type Query { a: Int }", "type Query { a: Int }\ntype A { id: ID }", &["safe: Object `A` was added"]),
+            ("type Query { a: Int }", "type Query { a: Int }\nscalar Date", &["safe: Scalar `Date` was added"]),
+            ("type Query { a: Int }", "type Query { a: Int, b: Int }", &["safe: Field `Query.b` was added"]),
+            ("type Query { a: Int }", "type Query { a: Int }\ntype Mutation { a: Int }", &["safe: Root mutation type `Mutation` was added", "safe: Object `Mutation` was added"]),
+            ("type Query { a: Int }", "type Query { a: Int! }", &["safe: Field `Query.a` changed type from `Int` to `Int!`"]),
+            ("type Query { a: [Int] }", "type Query { a: [Int!]! }", &["safe: Field `Query.a` changed type from `[Int]` to `[Int!]!`"]),
+            ("type Query { a(n: Int!): Int }", "type Query { a(n: Int): Int }", &["safe: Argument `Query.a(n)` changed type from `Int!` to `Int`"]),
+            ("type Query { a: Int }", "type Query { a: Int @deprecated(reason: \"Use b\") }", &["safe: `Query.a` was deprecated: Use b"]),
+            ("type Query { a: Int @deprecated }", "type Query { a: Int }", &["safe: `Query.a` is no longer deprecated"]),
+            ("type Query { e: E }\nenum E { A }", "type Query { e: E }\nenum E { A @deprecated }", &["safe: `E.A` was deprecated: No longer supported"])
+        ]);
+    }
+}
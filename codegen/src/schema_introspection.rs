@@ -1,99 +1,365 @@
 use serde::Deserialize;
 use serde_json::Deserializer;
 use serde_path_to_error::deserialize;
-use std::collections::BTreeSet;
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::diagnostics;
+use crate::diagnostics::Diagnostic;
 use crate::schema;
-use crate::schema::{ GqlDocument, Argument, Object, GqlType, Enum};
+use crate::schema::{ GqlDocument, Argument, Object, GqlType, Enum, Interface, Scalar, Union, RootTypes };
 
-pub fn from_response_body(response_body: &str) -> Result<GqlDocument, serde_path_to_error::Error<serde_json::Error>> {
-    let deserializer = &mut Deserializer::from_str(response_body);
-    let response: IntrospectionQueryResponse = deserialize(deserializer)?;
-    let types = response.data.schema.types;
+pub enum IntrospectionError {
+    Json(serde_path_to_error::Error<serde_json::Error>),
+    GraphQL(Vec<GraphQLError>),
+    MissingSchema,
+    TruncatedTypeRef(Vec<String>)
+}
+
+impl fmt::Display for IntrospectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntrospectionError::Json(error) => write!(f, "{}", error),
+            IntrospectionError::GraphQL(errors) => {
+                write!(f, "Introspection query returned {} error(s)", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  - {}", error)?;
+                }
+                Ok(())
+            }
+            IntrospectionError::MissingSchema => write!(f, "Introspection response contains no __schema, expected {{\"data\": {{\"__schema\": ...}}}} or {{\"__schema\": ...}}"),
+            IntrospectionError::TruncatedTypeRef(paths) => {
+                write!(f, "Introspection response has type references without ofType, the query did not resolve enough list and non-null wrappers")?;
+                for path in paths {
+                    write!(f, "\n  - {}", path)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for IntrospectionError {
+    fn from(error: serde_path_to_error::Error<serde_json::Error>) -> IntrospectionError {
+        IntrospectionError::Json(error)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GraphQLError {
+    message: String,
+    locations: Option<Vec<ErrorLocation>>,
+    path: Option<Vec<serde_json::Value>>
+}
+
+#[derive(Deserialize)]
+struct ErrorLocation {
+    line: u32,
+    column: u32
+}
+
+impl fmt::Display for GraphQLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(path) = &self.path {
+            let segments: Vec<String> = path
+                .iter()
+                .map(|segment| match segment {
+                    serde_json::Value::String(name) => name.clone(),
+                    other => other.to_string()
+                })
+                .collect();
+            write!(f, " (path: {})", segments.join("."))?;
+        }
+        if let Some(locations) = &self.locations {
+            let locations: Vec<String> = locations
+                .iter()
+                .map(|location| format!("{}:{}", location.line, location.column))
+                .collect();
+            write!(f, " (query location: {})", locations.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the document from an introspection response, returning it together
+/// with warnings for GraphQL errors the server returned alongside the schema
+/// and for custom directive definitions. Applied directives are not part of
+/// introspection, so unlike SDL there are no skipped directive usages to report.
+pub fn from_response_body(response_body: &str) -> Result<(GqlDocument, Vec<Diagnostic>), IntrospectionError> {
+    let (schema, errors) = read_schema(response_body)?;
+    let mut warnings: Vec<Diagnostic> = errors
+        .iter()
+        .map(|error| {
+            let message = format!("Introspection returned an error alongside the schema: {}", error);
+            Diagnostic::warning(diagnostics::PARTIAL_INTROSPECTION, message)
+        })
+        .collect();
+    for directive in schema.directives.iter().filter(|directive| !schema::BUILT_IN_DIRECTIVES.contains(&directive.name.as_str())) {
+        let message = format!("Directive definition @{} is not represented in generated code", directive.name);
+        warnings.push(Diagnostic::warning(diagnostics::SKIPPED_DIRECTIVE_DEFINITION, message));
+    }
+    let truncated_paths: Vec<String> = schema.types
+        .iter()
+        .flat_map(truncated_type_ref_paths)
+        .collect();
+    if !truncated_paths.is_empty() {
+        return Err(IntrospectionError::TruncatedTypeRef(truncated_paths));
+    }
+    let types = schema.types;
+    let root_types = RootTypes {
+        query: schema.query_type.map(|it| it.name),
+        mutation: schema.mutation_type.map(|it| it.name),
+        subscription: schema.subscription_type.map(|it| it.name)
+    };
 
     let mut enums: Vec<Enum> = Vec::new();
-    let mut scalars: BTreeSet<String> = BTreeSet::new();
+    let mut scalars: BTreeMap<String, Scalar> = BTreeMap::new();
     let mut inputs: Vec<Object> = Vec::new();
     let mut outputs: Vec<Object> = Vec::new();
+    let mut interfaces: Vec<Interface> = Vec::new();
+    let mut unions: Vec<Union> = Vec::new();
     
     for scalar in schema::BUILT_IN_SCALARS {
-        scalars.insert(scalar.to_string());
+        scalars.insert(scalar.to_string(), Scalar { description: None, specified_by_url: None });
     }
 
     for gql_type in types {
         match gql_type {
-            FullType::Enum { name, enum_values, .. } => {
-                enums.push(Enum { name, values: enum_values.iter().map(|it| it.name.clone()).collect() });
-            }
-            FullType::Object { name, fields, .. } => {
-                let object_fields = fields
-                    .iter()
-                    .map(|field| {
-                        let field_name = field.name.clone();
-                        let field_type = to_gql_type(&field.field_type, true);
-                        if field.args.is_empty() {
-                            schema::Field { name: field_name, field_type }
-                        } else {
-
-                            let args = field.args
-                                .iter()
-                                .map(|arg| {
-                                    let arg_name = arg.name.clone();
-                                    let arg_type = to_gql_type(&arg.input_type, true);
-                                    let type_name = gql_type_name(&arg.input_type);
-                                    Argument { name: arg_name, argument_type: arg_type, type_name }
-                                })
-                                .collect();
-
-                            let fn_type = GqlType::Function {
-                                inputs: args,
-                                output: Box::new(field_type)
-                            };
-
-                             schema::Field { name: field_name, field_type: fn_type }
-                        }
+            FullType::Enum { name, description, enum_values } => {
+                let values = enum_values
+                    .into_iter()
+                    .map(|it| {
+                        let deprecated = deprecation(it.is_deprecated, it.deprecation_reason);
+                        schema::EnumValue { name: it.name, description: it.description, deprecated }
                     })
                     .collect();
-
-                outputs.push(Object { name, fields: object_fields });
+                enums.push(Enum { name, description, values });
+            }
+            FullType::Object { name, description, fields } => {
+                outputs.push(Object { name, description, fields: to_output_fields(&fields), one_of: false });
+            }
+            FullType::Interface { name, description, fields, interfaces: implemented, possible_types } => {
+                let implemented = implemented.unwrap_or_default().iter().map(gql_type_name).collect();
+                let implementors = possible_types.iter().map(gql_type_name).collect();
+                let fields = to_output_fields(&fields);
+                interfaces.push(Interface { name, description, fields, interfaces: implemented, implementors });
             }
-            FullType::Scalar { name, .. } => {
-                scalars.insert(name);
+            FullType::Scalar { name, description, specified_by_url } => {
+                scalars.insert(name, Scalar { description, specified_by_url });
             }
-            FullType::InputObject { name, input_fields, .. } => {
+            FullType::InputObject { name, description, input_fields, is_one_of } => {
                 let fields = input_fields
                     .iter()
                     .map(|field| {
                         let field_name = &field.name;
                         let field_type = to_gql_type(&field.input_type, true);
-                        schema::Field { name: field_name.clone(), field_type }
+                        schema::Field {
+                            name: field_name.clone(),
+                            description: field.description.clone(),
+                            deprecated: deprecation(field.is_deprecated, field.deprecation_reason.clone()),
+                            default_value: field.default_value.clone(),
+                            field_type
+                        }
                     })
                     .collect();
-                inputs.push(Object { name, fields });
+                inputs.push(Object { name, description, fields, one_of: is_one_of == Some(true) });
+            }
+            FullType::Union { name, description, possible_types } => {
+                let members = possible_types.iter().map(gql_type_name).collect();
+                unions.push(Union { name, description, members });
             }
-            FullType::Interface { .. } => (),
-            FullType::Union { .. } => ()
         }
     }
-    Ok(GqlDocument { inputs, outputs, enums, scalars })
+    if let Some(query_type) = &root_types.query {
+        add_introspection_root_fields(&mut outputs, query_type);
+    }
+    Ok((GqlDocument { inputs, outputs, interfaces, unions, enums, scalars, root_types }, warnings))
+}
+
+/// Names of types with a field, argument or input field whose type reference
+/// ends in a list or non-null wrapper without `ofType`.
+pub fn truncated_type_names(response_body: &str) -> Result<Vec<String>, IntrospectionError> {
+    let (schema, _) = read_schema(response_body)?;
+    let names = schema.types
+        .iter()
+        .filter(|full_type| !truncated_type_ref_paths(full_type).is_empty())
+        .map(|full_type| full_type.name().to_string())
+        .collect();
+    Ok(names)
+}
+
+/// A response may carry errors next to a usable schema, those are returned
+/// with it and only fail the read when there is no schema.
+fn read_schema(response_body: &str) -> Result<(Schema, Vec<GraphQLError>), IntrospectionError> {
+    let deserializer = &mut Deserializer::from_str(response_body);
+    let response: IntrospectionQueryResponse = deserialize(deserializer)?;
+    let errors = response.errors.unwrap_or_default();
+    match (response.data, response.schema) {
+        (Some(SchemaData { schema: Some(schema) }), _) => Ok((schema, errors)),
+        (_, Some(schema)) => Ok((schema, errors)),
+        _ if !errors.is_empty() => Err(IntrospectionError::GraphQL(errors)),
+        _ => Err(IntrospectionError::MissingSchema)
+    }
+}
+
+fn truncated_type_ref_paths(full_type: &FullType) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    match full_type {
+        FullType::Object { name, fields, .. } | FullType::Interface { name, fields, .. } => {
+            for field in fields {
+                if is_truncated(&field.field_type) {
+                    paths.push(format!("{}.{}", name, field.name));
+                }
+                for arg in &field.args {
+                    if is_truncated(&arg.input_type) {
+                        paths.push(format!("{}.{}({})", name, field.name, arg.name));
+                    }
+                }
+            }
+        }
+        FullType::InputObject { name, input_fields, .. } => {
+            for field in input_fields {
+                if is_truncated(&field.input_type) {
+                    paths.push(format!("{}.{}", name, field.name));
+                }
+            }
+        }
+        FullType::Enum { .. } | FullType::Scalar { .. } | FullType::Union { .. } => {}
+    }
+    paths
+}
+
+fn is_truncated(type_ref: &TypeRef) -> bool {
+    match type_ref {
+        TypeRef::NonNull { of_type } | TypeRef::List { of_type } => match of_type {
+            Some(of_type) => is_truncated(of_type),
+            None => true
+        },
+        _ => false
+    }
+}
+
+fn add_introspection_root_fields(outputs: &mut [Object], query_type: &str) {
+    let has_schema_type = outputs.iter().any(|object| object.name == "__Schema");
+    let has_type_type = outputs.iter().any(|object| object.name == "__Type");
+    let query = match outputs.iter_mut().find(|object| object.name == query_type) {
+        Some(query) => query,
+        None => return
+    };
+    if has_schema_type {
+        query.fields.push(schema::Field {
+            name: "__schema".to_string(),
+            description: None,
+            deprecated: None,
+            default_value: None,
+            field_type: GqlType::Object("__Schema".to_string())
+        });
+    }
+    if has_type_type {
+        let name_argument = Argument {
+            name: "name".to_string(),
+            description: None,
+            deprecated: None,
+            default_value: None,
+            argument_type: GqlType::Scalar("String".to_string()),
+            type_name: "String!".to_string()
+        };
+        query.fields.push(schema::Field {
+            name: "__type".to_string(),
+            description: None,
+            deprecated: None,
+            default_value: None,
+            field_type: GqlType::Function {
+                inputs: vec![name_argument],
+                output: Box::new(GqlType::Nullable(Box::new(GqlType::Object("__Type".to_string()))))
+            }
+        });
+    }
+}
+
+fn to_output_fields(fields: &[Field]) -> Vec<schema::Field> {
+    fields
+        .iter()
+        .map(|field| {
+            let field_name = field.name.clone();
+            let description = field.description.clone();
+            let deprecated = deprecation(field.is_deprecated, field.deprecation_reason.clone());
+            let field_type = to_gql_type(&field.field_type, true);
+            if field.args.is_empty() {
+                schema::Field { name: field_name, description, deprecated, default_value: None, field_type }
+            } else {
+
+                let args = field.args
+                    .iter()
+                    .map(|arg| {
+                        let arg_name = arg.name.clone();
+                        let arg_type = to_gql_type(&arg.input_type, true);
+                        let type_name = gql_type_name(&arg.input_type);
+                        Argument {
+                            name: arg_name,
+                            description: arg.description.clone(),
+                            deprecated: deprecation(arg.is_deprecated, arg.deprecation_reason.clone()),
+                            default_value: arg.default_value.clone(),
+                            argument_type: arg_type,
+                            type_name
+                        }
+                    })
+                    .collect();
+
+                let fn_type = GqlType::Function {
+                    inputs: args,
+                    output: Box::new(field_type)
+                };
+
+                schema::Field { name: field_name, description, deprecated, default_value: None, field_type: fn_type }
+            }
+        })
+        .collect()
+}
+
+fn deprecation(is_deprecated: Option<bool>, reason: Option<String>) -> Option<String> {
+    if is_deprecated == Some(true) {
+        Some(reason.unwrap_or_else(|| schema::DEFAULT_DEPRECATION_REASON.to_string()))
+    } else {
+        None
+    }
 }
 
 #[derive(Deserialize)]
-#[serde(deny_unknown_fields)]
 struct IntrospectionQueryResponse {
-    data: SchemaData,
+    data: Option<SchemaData>,
+    #[serde(rename = "__schema")]
+    schema: Option<Schema>,
+    errors: Option<Vec<GraphQLError>>
 }
 
 #[derive(Deserialize)]
-#[serde(deny_unknown_fields)]
 struct SchemaData {
     #[serde(rename = "__schema")]
-    schema: Schema,
+    schema: Option<Schema>,
 }
 
 #[derive(Deserialize)]
-#[serde(deny_unknown_fields)]
 struct Schema {
-    types: Vec<FullType>
+    #[serde(rename = "queryType")]
+    query_type: Option<RootTypeRef>,
+    #[serde(rename = "mutationType")]
+    mutation_type: Option<RootTypeRef>,
+    #[serde(rename = "subscriptionType")]
+    subscription_type: Option<RootTypeRef>,
+    types: Vec<FullType>,
+    #[serde(default)]
+    directives: Vec<DirectiveDefinition>
+}
+
+#[derive(Deserialize)]
+struct DirectiveDefinition {
+    name: String
+}
+
+#[derive(Deserialize)]
+struct RootTypeRef {
+    name: String
 }
 
 #[derive(Deserialize)]
@@ -102,45 +368,89 @@ enum FullType {
     #[serde(rename = "OBJECT")]
     Object {
         name: String,
+        description: Option<String>,
         fields: Vec<Field>
     },
     #[serde(rename = "INTERFACE")]
-    Interface,
+    Interface {
+        name: String,
+        description: Option<String>,
+        fields: Vec<Field>,
+        /// Null from servers that predate interfaces implementing interfaces.
+        interfaces: Option<Vec<TypeRef>>,
+        #[serde(rename = "possibleTypes")]
+        possible_types: Vec<TypeRef>
+    },
     #[serde(rename = "ENUM")]
     Enum {
         name: String,
+        description: Option<String>,
         #[serde(rename = "enumValues")]
         enum_values: Vec<EnumValue>
     },
     #[serde(rename = "INPUT_OBJECT")]
     InputObject {
         name: String,
+        description: Option<String>,
         #[serde(rename = "inputFields")]
-        input_fields: Vec<InputValue>
+        input_fields: Vec<InputValue>,
+        #[serde(rename = "isOneOf")]
+        is_one_of: Option<bool>
     },
     #[serde(rename = "SCALAR")]
     Scalar {
-        name: String
+        name: String,
+        description: Option<String>,
+        #[serde(rename = "specifiedByURL")]
+        specified_by_url: Option<String>
     },
     #[serde(rename = "UNION")]
-    Union,
+    Union {
+        name: String,
+        description: Option<String>,
+        #[serde(rename = "possibleTypes")]
+        possible_types: Vec<TypeRef>
+    },
+}
+
+impl FullType {
+    fn name(&self) -> &str {
+        match self {
+            FullType::Object { name, .. } => name,
+            FullType::Interface { name, .. } => name,
+            FullType::Enum { name, .. } => name,
+            FullType::InputObject { name, .. } => name,
+            FullType::Scalar { name, .. } => name,
+            FullType::Union { name, .. } => name
+        }
+    }
 }
 
 #[derive(Deserialize)]
-#[serde(deny_unknown_fields)]
 struct Field {
     name: String,
+    description: Option<String>,
+    #[serde(rename = "isDeprecated")]
+    is_deprecated: Option<bool>,
+    #[serde(rename = "deprecationReason")]
+    deprecation_reason: Option<String>,
     args: Vec<InputValue>,
     #[serde(rename = "type")]
     field_type: TypeRef,
 }
 
 #[derive(Deserialize)]
-#[serde(deny_unknown_fields)]
 struct InputValue {
     name: String,
+    description: Option<String>,
+    #[serde(rename = "isDeprecated")]
+    is_deprecated: Option<bool>,
+    #[serde(rename = "deprecationReason")]
+    deprecation_reason: Option<String>,
     #[serde(rename = "type")]
-    input_type: TypeRef
+    input_type: TypeRef,
+    #[serde(rename = "defaultValue")]
+    default_value: Option<String>
 }
 
 #[derive(Deserialize)]
@@ -149,7 +459,7 @@ enum TypeRef {
     #[serde(rename = "NON_NULL")]
     NonNull {
         #[serde(rename = "ofType")]
-        of_type: Box<TypeRef>
+        of_type: Option<Box<TypeRef>>
     },
     #[serde(rename = "SCALAR")]
     Scalar {
@@ -162,12 +472,20 @@ enum TypeRef {
     #[serde(rename = "LIST")]
     List {
         #[serde(rename = "ofType")]
-        of_type: Box<TypeRef>,
+        of_type: Option<Box<TypeRef>>,
     },
     #[serde(rename = "OBJECT")]
     Object {
         name: String
     },
+    #[serde(rename = "INTERFACE")]
+    Interface {
+        name: String
+    },
+    #[serde(rename = "UNION")]
+    Union {
+        name: String
+    },
     #[serde(rename = "ENUM")]
     Enum {
         name: String
@@ -175,9 +493,13 @@ enum TypeRef {
 }
 
 #[derive(Deserialize)]
-#[serde(deny_unknown_fields)]
 struct EnumValue {
     name: String,
+    description: Option<String>,
+    #[serde(rename = "isDeprecated")]
+    is_deprecated: Option<bool>,
+    #[serde(rename = "deprecationReason")]
+    deprecation_reason: Option<String>,
 }
 
 fn to_gql_type(type_ref: &TypeRef, nullable: bool) -> GqlType {
@@ -191,7 +513,7 @@ fn to_gql_type(type_ref: &TypeRef, nullable: bool) -> GqlType {
             }
         }
         TypeRef::List { of_type, .. } => {
-            let inner = to_gql_type(of_type, true);
+            let inner = to_gql_type(wrapped_type(of_type), true);
             if nullable {
                 GqlType::Nullable(Box::new(GqlType::List(Box::new(inner))))
             } else {
@@ -206,7 +528,7 @@ fn to_gql_type(type_ref: &TypeRef, nullable: bool) -> GqlType {
                 inner
             }
         }
-        TypeRef::NonNull { of_type, .. } => to_gql_type(of_type, false),
+        TypeRef::NonNull { of_type, .. } => to_gql_type(wrapped_type(of_type), false),
         TypeRef::Scalar { name, .. } => {
             let inner = GqlType::Scalar(name.clone());
             if nullable {
@@ -215,7 +537,7 @@ fn to_gql_type(type_ref: &TypeRef, nullable: bool) -> GqlType {
                 inner
             }
         },
-        TypeRef::Object { name, .. } => {
+        TypeRef::Object { name, .. } | TypeRef::Interface { name, .. } | TypeRef::Union { name, .. } => {
             let inner = GqlType::Object(name.clone());
             if nullable {
                 GqlType::Nullable(Box::new(inner))
@@ -230,9 +552,88 @@ fn gql_type_name(type_ref: &TypeRef) -> String {
     match type_ref {
         TypeRef::Scalar { name, .. } => name.clone(),
         TypeRef::Object { name, .. } => name.clone(),
+        TypeRef::Interface { name, .. } => name.clone(),
+        TypeRef::Union { name, .. } => name.clone(),
         TypeRef::Enum { name, .. } => name.clone(),
         TypeRef::InputObject { name, .. } => name.clone(),
-        TypeRef::List { of_type, .. } => format!("[{}]", gql_type_name(of_type)),
-        TypeRef::NonNull { of_type, .. } => format!("{}!", gql_type_name(of_type)),
+        TypeRef::List { of_type, .. } => format!("[{}]", gql_type_name(wrapped_type(of_type))),
+        TypeRef::NonNull { of_type, .. } => format!("{}!", gql_type_name(wrapped_type(of_type))),
+    }
+}
+
+fn wrapped_type(of_type: &Option<Box<TypeRef>>) -> &TypeRef {
+    of_type
+        .as_deref()
+        .expect("Truncated type references are rejected before conversion")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{"queryType":{"name":"Query"},"mutationType":null,"subscriptionType":null,"types":[
+        {"kind":"OBJECT","name":"Query","description":null,"fields":[
+            {"name":"hello","description":null,"isDeprecated":false,"deprecationReason":null,"args":[],"type":{"kind":"SCALAR","name":"String","ofType":null}}
+        ]}
+    ]}"#;
+
+    #[test]
+    fn errors_alongside_schema_are_warnings() {
+        let body = format!(r#"{{"data":{{"__schema":{}}},"errors":[{{"message":"Field resolver failed","path":["__schema","types"]}}]}}"#, SCHEMA);
+        let (document, warnings) = from_response_body(&body).ok().unwrap();
+        assert_eq!(document.outputs[0].name, "Query");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, diagnostics::PARTIAL_INTROSPECTION);
+        assert!(warnings[0].message.contains("Field resolver failed (path: __schema.types)"));
+    }
+
+    #[test]
+    fn warns_about_custom_directive_definitions() {
+        let schema = SCHEMA.replacen('{', r#"{"directives":[{"name":"include"},{"name":"deprecated"},{"name":"cacheControl"}],"#, 1);
+        let body = format!(r#"{{"data":{{"__schema":{}}}}}"#, schema);
+        let (_, warnings) = from_response_body(&body).ok().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, diagnostics::SKIPPED_DIRECTIVE_DEFINITION);
+        assert!(warnings[0].message.contains("@cacheControl"));
+    }
+
+    #[test]
+    fn errors_without_schema_fail() {
+        let body = r#"{"data":null,"errors":[{"message":"Not authorized"}]}"#;
+        match from_response_body(body) {
+            Err(error @ IntrospectionError::GraphQL(_)) => assert!(error.to_string().contains("Not authorized")),
+            _ => panic!("expected GraphQL errors")
+        }
+        assert!(matches!(from_response_body(r#"{"data":null}"#), Err(IntrospectionError::MissingSchema)));
+    }
+
+    const TRUNCATED_SCHEMA: &str = r#"{"__schema":{"queryType":{"name":"Query"},"mutationType":null,"subscriptionType":null,"types":[
+        {"kind":"OBJECT","name":"Query","description":null,"fields":[
+            {"name":"matrix","description":null,"args":[],"type":{"kind":"NON_NULL","name":null,"ofType":{"kind":"LIST","name":null,"ofType":null}}},
+            {"name":"search","description":null,"args":[
+                {"name":"ids","description":null,"type":{"kind":"LIST","name":null,"ofType":null},"defaultValue":null}
+            ],"type":{"kind":"SCALAR","name":"String","ofType":null}}
+        ]},
+        {"kind":"OBJECT","name":"User","description":null,"fields":[
+            {"name":"tags","description":null,"args":[],"type":{"kind":"LIST","name":null,"ofType":{"kind":"SCALAR","name":"String","ofType":null}}}
+        ]},
+        {"kind":"INPUT_OBJECT","name":"Filter","description":null,"inputFields":[
+            {"name":"tags","description":null,"type":{"kind":"NON_NULL","name":null,"ofType":null},"defaultValue":null}
+        ]}
+    ]}}"#;
+
+    #[test]
+    fn names_types_with_truncated_type_references() {
+        assert_eq!(truncated_type_names(TRUNCATED_SCHEMA).ok().unwrap(), ["Query", "Filter"]);
+        let body = format!(r#"{{"data":{{"__schema":{}}}}}"#, SCHEMA);
+        assert!(truncated_type_names(&body).ok().unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_type_references_before_conversion() {
+        match from_response_body(TRUNCATED_SCHEMA) {
+            Err(IntrospectionError::TruncatedTypeRef(paths)) => assert_eq!(paths, ["Query.matrix", "Query.search(ids)", "Filter.tags"]),
+            _ => panic!("expected truncated type references")
+        }
     }
 }
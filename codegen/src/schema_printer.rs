@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use crate::code_writer::{CodeFile, CodeFileOptions};
+use crate::schema;
+use crate::schema::{Argument, Enum, Field, GqlDocument, GqlType, Interface, Object, RootTypes, Scalar, Union};
+
+/// Renders the document as SDL, grouped by kind in the order of the document
+/// so the output follows the configured type and field order. Introspection
+/// types are left out as their names are reserved.
+pub fn print_document(document: &GqlDocument, options: &CodeFileOptions) -> String {
+    let GqlDocument { inputs, outputs, interfaces, unions, enums, scalars, root_types } = document;
+    let mut file = CodeFile::new(options);
+    let mut blocks: usize = 0;
+    let mut next_block = |file: &mut CodeFile| {
+        if blocks > 0 {
+            file.blank_line();
+        }
+        blocks += 1;
+    };
+
+    if has_custom_root_types(root_types) {
+        next_block(&mut file);
+        write_schema_definition(&mut file, root_types);
+    }
+    for (name, scalar) in scalars.iter().filter(|(name, _)| !schema::BUILT_IN_SCALARS.contains(&name.as_str())) {
+        next_block(&mut file);
+        write_scalar(&mut file, name, scalar);
+    }
+    for enum_def in enums.iter().filter(|enum_def| !schema::is_introspection_name(&enum_def.name)) {
+        next_block(&mut file);
+        write_enum(&mut file, enum_def);
+    }
+    for interface in interfaces.iter().filter(|interface| !schema::is_introspection_name(&interface.name)) {
+        next_block(&mut file);
+        write_interface(&mut file, interface);
+    }
+    let implemented = implemented_interfaces(interfaces);
+    for output in outputs.iter().filter(|output| !schema::is_introspection_name(&output.name)) {
+        next_block(&mut file);
+        write_output_object(&mut file, output, implemented.get(output.name.as_str()));
+    }
+    for union in unions.iter().filter(|union| !schema::is_introspection_name(&union.name)) {
+        next_block(&mut file);
+        write_union(&mut file, union);
+    }
+    for input in inputs.iter().filter(|input| !schema::is_introspection_name(&input.name)) {
+        next_block(&mut file);
+        write_input_object(&mut file, input);
+    }
+    file.build_string()
+}
+
+fn has_custom_root_types(root_types: &RootTypes) -> bool {
+    let is_custom = |type_name: &Option<String>, default_name: &str| {
+        type_name.as_ref().is_some_and(|type_name| type_name != default_name)
+    };
+    is_custom(&root_types.query, schema::DEFAULT_QUERY_TYPE)
+        || is_custom(&root_types.mutation, schema::DEFAULT_MUTATION_TYPE)
+        || is_custom(&root_types.subscription, schema::DEFAULT_SUBSCRIPTION_TYPE)
+}
+
+fn write_schema_definition(file: &mut CodeFile, root_types: &RootTypes) {
+    let operations = [
+        ("query", &root_types.query),
+        ("mutation", &root_types.mutation),
+        ("subscription", &root_types.subscription)
+    ];
+    file.begin_indent("schema {");
+    for (operation, type_name) in operations {
+        if let Some(type_name) = type_name {
+            file.line(&format!("{}: {}", operation, type_name));
+        }
+    }
+    file.end_indent("}");
+}
+
+fn write_scalar(file: &mut CodeFile, name: &str, scalar: &Scalar) {
+    write_description(file, &scalar.description);
+    match &scalar.specified_by_url {
+        Some(url) => file.line(&format!("scalar {} @specifiedBy(url: {})", name, string_literal(url))),
+        None => file.line(&format!("scalar {}", name))
+    }
+}
+
+fn write_enum(file: &mut CodeFile, enum_def: &Enum) {
+    write_description(file, &enum_def.description);
+    file.begin_indent(&format!("enum {} {{", enum_def.name));
+    for value in &enum_def.values {
+        write_description(file, &value.description);
+        file.line(&format!("{}{}", value.name, deprecated_directive(&value.deprecated)));
+    }
+    file.end_indent("}");
+}
+
+fn write_interface(file: &mut CodeFile, interface: &Interface) {
+    write_description(file, &interface.description);
+    file.begin_indent(&format!("interface {}{} {{", interface.name, implements_clause(&interface.interfaces)));
+    write_output_fields(file, &interface.fields);
+    file.end_indent("}");
+}
+
+fn implemented_interfaces(interfaces: &[Interface]) -> HashMap<&str, Vec<&str>> {
+    let mut implemented: HashMap<&str, Vec<&str>> = HashMap::new();
+    for interface in interfaces {
+        for implementor in &interface.implementors {
+            implemented.entry(implementor.as_str()).or_default().push(interface.name.as_str());
+        }
+    }
+    implemented
+}
+
+fn write_output_object(file: &mut CodeFile, object: &Object, interfaces: Option<&Vec<&str>>) {
+    write_description(file, &object.description);
+    let implements = match interfaces {
+        Some(interfaces) => implements_clause(interfaces),
+        None => String::new()
+    };
+    file.begin_indent(&format!("type {}{} {{", object.name, implements));
+    write_output_fields(file, &object.fields);
+    file.end_indent("}");
+}
+
+fn implements_clause<T: AsRef<str>>(interfaces: &[T]) -> String {
+    if interfaces.is_empty() {
+        return String::new();
+    }
+    let names: Vec<&str> = interfaces.iter().map(|name| name.as_ref()).collect();
+    format!(" implements {}", names.join(" & "))
+}
+
+fn write_output_fields(file: &mut CodeFile, fields: &[Field]) {
+    for field in fields.iter().filter(|field| !schema::is_introspection_name(&field.name)) {
+        write_description(file, &field.description);
+        let deprecated = deprecated_directive(&field.deprecated);
+        match &field.field_type {
+            GqlType::Function { inputs, output } => {
+                let arguments = arguments_to_sdl(inputs);
+                if inputs.iter().any(|input| input.description.is_some()) {
+                    file.begin_indent(&format!("{}(", field.name));
+                    for argument in inputs {
+                        write_description(file, &argument.description);
+                        file.line(&argument_to_sdl(argument));
+                    }
+                    file.end_indent(&format!("): {}{}", type_to_sdl(output), deprecated));
+                } else {
+                    file.line(&format!("{}({}): {}{}", field.name, arguments, type_to_sdl(output), deprecated));
+                }
+            }
+            field_type => file.line(&format!("{}: {}{}", field.name, type_to_sdl(field_type), deprecated))
+        }
+    }
+}
+
+fn arguments_to_sdl(arguments: &[Argument]) -> String {
+    let arguments: Vec<String> = arguments.iter().map(argument_to_sdl).collect();
+    arguments.join(", ")
+}
+
+fn argument_to_sdl(argument: &Argument) -> String {
+    let default_value = match &argument.default_value {
+        Some(default_value) => format!(" = {}", default_value),
+        None => String::new()
+    };
+    format!("{}: {}{}{}", argument.name, argument.type_name, default_value, deprecated_directive(&argument.deprecated))
+}
+
+fn write_union(file: &mut CodeFile, union: &Union) {
+    write_description(file, &union.description);
+    file.line(&format!("union {} = {}", union.name, union.members.join(" | ")));
+}
+
+fn write_input_object(file: &mut CodeFile, input: &Object) {
+    write_description(file, &input.description);
+    let one_of = if input.one_of { " @oneOf" } else { "" };
+    file.begin_indent(&format!("input {}{} {{", input.name, one_of));
+    for field in &input.fields {
+        write_description(file, &field.description);
+        let default_value = match &field.default_value {
+            Some(default_value) => format!(" = {}", default_value),
+            None => String::new()
+        };
+        let deprecated = deprecated_directive(&field.deprecated);
+        file.line(&format!("{}: {}{}{}", field.name, type_to_sdl(&field.field_type), default_value, deprecated));
+    }
+    file.end_indent("}");
+}
+
+fn write_description(file: &mut CodeFile, description: &Option<String>) {
+    let description = match description {
+        Some(description) => description,
+        None => return
+    };
+    if description.contains('\n') || description.contains('"') {
+        file.line("\"\"\"");
+        for line in description.replace("\"\"\"", "\\\"\"\"").lines() {
+            file.line(line);
+        }
+        file.line("\"\"\"");
+    } else {
+        file.line(&format!("\"{}\"", description.replace('\\', "\\\\")));
+    }
+}
+
+fn deprecated_directive(deprecated: &Option<String>) -> String {
+    match deprecated {
+        Some(reason) if reason == schema::DEFAULT_DEPRECATION_REASON => " @deprecated".to_string(),
+        Some(reason) => format!(" @deprecated(reason: {})", string_literal(reason)),
+        None => String::new()
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+pub fn type_to_sdl(gql_type: &GqlType) -> String {
+    match gql_type {
+        GqlType::Nullable(inner) => nullable_type_to_sdl(inner),
+        gql_type => format!("{}!", nullable_type_to_sdl(gql_type))
+    }
+}
+
+fn nullable_type_to_sdl(gql_type: &GqlType) -> String {
+    match gql_type {
+        GqlType::List(inner) => format!("[{}]", type_to_sdl(inner)),
+        GqlType::Object(name) | GqlType::Scalar(name) | GqlType::Enum(name) => name.clone(),
+        GqlType::Nullable(inner) => nullable_type_to_sdl(inner),
+        GqlType::Function { output, .. } => nullable_type_to_sdl(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SchemaSource;
+    use crate::schema_introspection;
+    use crate::schema_sdl;
+
+    const SDL: &str = r#"schema {
+    query: Root
+}
+
+"Calendar date"
+scalar Date @specifiedBy(url: "https://tools.ietf.org/html/rfc3339")
+
+scalar Json
+
+enum Role {
+    ADMIN
+    "Read only"
+    GUEST @deprecated(reason: "Use ADMIN")
+}
+
+"Has an id"
+interface Node {
+    id: ID!
+}
+
+interface Entity implements Node {
+    id: ID!
+    created: Date
+}
+
+type Root {
+    user(id: ID!, role: Role = ADMIN): User
+    search(
+        "Text to search"
+        text: String!
+    ): [Result!]! @deprecated
+}
+
+type User implements Node & Entity {
+    id: ID!
+    created: Date
+    name: String @deprecated(reason: "Use \"fullName\"")
+}
+
+union Result = User
+
+input Filter @oneOf {
+    role: Role
+    text: String = "none" @deprecated
+}
+"#;
+
+    fn options() -> CodeFileOptions {
+        CodeFileOptions { line_break: "\n".to_string(), indent: "    ".to_string() }
+    }
+
+    fn print_sdl(sdl: &str) -> String {
+        let sources = [SchemaSource { name: None, content: sdl.to_string() }];
+        let (document, _) = schema_sdl::from_sdl_sources(&sources).ok().unwrap();
+        print_document(&document, &options())
+    }
+
+    #[test]
+    fn round_trips_sdl() {
+        let printed = print_sdl(SDL);
+        assert_eq!(printed, SDL);
+        assert_eq!(print_sdl(&printed), printed);
+    }
+
+    #[test]
+    fn prints_introspected_interfaces_and_scalars() {
+        let body = r#"{"data":{"__schema":{"queryType":{"name":"Query"},"types":[
+            {"kind":"SCALAR","name":"Date","description":"Calendar date","specifiedByURL":"https://example.com/date"},
+            {"kind":"INTERFACE","name":"Node","description":null,"interfaces":[],"possibleTypes":[],"fields":[
+                {"name":"id","description":null,"isDeprecated":false,"deprecationReason":null,"args":[],"type":{"kind":"SCALAR","name":"ID","ofType":null}}
+            ]},
+            {"kind":"INTERFACE","name":"Entity","description":null,"interfaces":[{"kind":"INTERFACE","name":"Node","ofType":null}],"possibleTypes":[],"fields":[
+                {"name":"id","description":null,"isDeprecated":false,"deprecationReason":null,"args":[],"type":{"kind":"SCALAR","name":"ID","ofType":null}}
+            ]},
+            {"kind":"OBJECT","name":"Query","description":null,"fields":[
+                {"name":"today","description":null,"isDeprecated":false,"deprecationReason":null,"args":[],"type":{"kind":"SCALAR","name":"Date","ofType":null}}
+            ]}
+        ]}}}"#;
+        let (document, _) = schema_introspection::from_response_body(body).ok().unwrap();
+        let printed = print_document(&document, &options());
+        assert!(printed.contains("\"Calendar date\"\nscalar Date @specifiedBy(url: \"https://example.com/date\")\n"));
+        assert!(printed.contains("interface Entity implements Node {\n"));
+        assert!(printed.contains("interface Node {\n"));
+    }
+}
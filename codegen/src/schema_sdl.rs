@@ -1,30 +1,86 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, HashMap};
+use graphql_parser::Pos;
 use graphql_parser::schema::ParseError;
-use graphql_parser::schema::{Document, TypeDefinition, Type, InputObjectType, ObjectType};
-use graphql_parser::schema::Definition;
-use crate::schema::{ GqlDocument, Argument, GqlType, Enum, Field, Object };
+use graphql_parser::schema::{Document, TypeDefinition, TypeExtension, Type, InputObjectType, ObjectType, InterfaceType, SchemaDefinition};
+use graphql_parser::schema::{Definition, Directive, Value};
+use crate::schema::{ GqlDocument, Argument, GqlType, Enum, EnumValue, Field, Object, Interface, Scalar, Union, RootTypes, SchemaSource, ReferenceKind };
+use crate::diagnostics::{self, Diagnostic};
 use graphql_parser::schema::parse_schema;
 use crate::schema;
 
-pub fn from_sdl_string(sdl: &str) -> Result<GqlDocument, ParseError> {
-    let schema = parse_schema(sdl)?;
-    Ok(from_parser_document(schema))
-}
+/// Directives that are reflected in the generated code, or that carry nothing
+/// a client needs, and therefore are not reported as skipped.
+const HANDLED_DIRECTIVES: &[&str] = &["deprecated", "oneOf", "specifiedBy"];
 
-fn from_parser_document(document: Document<'_, String>) -> GqlDocument {
+/// Builds one document from all sources, returning it together with warnings
+/// for schema constructs that are left out of the generated code.
+pub fn from_sdl_sources(sources: &[SchemaSource]) -> Result<(GqlDocument, Vec<Diagnostic>), Vec<Diagnostic>> {
     let mut builder = GqlDocumentBuilder::new();
+    let mut parse_errors: Vec<Diagnostic> = Vec::new();
     for scalar in schema::BUILT_IN_SCALARS {
         builder.add_scalar(scalar);
     }
-    builder.add_document(document);
+    for source in sources {
+        match parse_schema(&source.content) {
+            Ok(document) => builder.add_document(document, &source.name),
+            Err(error) => parse_errors.push(to_parse_diagnostic(error, &source.name))
+        }
+    }
+    if !parse_errors.is_empty() {
+        return Err(parse_errors);
+    }
     builder.build()
 }
 
+/// graphql-parser keeps its error opaque, the position is only available
+/// from the rendered `Parse error at line:column` prefix.
+fn to_parse_diagnostic(error: ParseError, source_name: &Option<String>) -> Diagnostic {
+    let rendered = error.to_string();
+    let rendered = rendered.trim_start_matches("schema parse error: ");
+    let (first_line, rest) = rendered.split_once('\n').unwrap_or((rendered, ""));
+    let position = first_line
+        .strip_prefix("Parse error at ")
+        .and_then(|position| position.split_once(':'))
+        .and_then(|(line, column)| Some(Pos { line: line.parse().ok()?, column: column.parse().ok()? }));
+    let message = match position {
+        Some(_) if !rest.trim().is_empty() => rest.trim().replace('\n', ", "),
+        _ => rendered.trim().replace('\n', ", ")
+    };
+    Diagnostic::error(diagnostics::SYNTAX_ERROR, message).at(source_name, position)
+}
+
+struct TypeContext<'c> {
+    path: String,
+    source_name: &'c Option<String>,
+    position: Pos
+}
+
+/// A union member or `implements` target, checked once all sources and
+/// extensions are read.
+struct TypeReference {
+    name: String,
+    kind: ReferenceKind,
+    path: String,
+    source_name: Option<String>,
+    position: Pos
+}
+
 struct GqlDocumentBuilder<'a> {
     input_definitions: BTreeMap<String, InputObjectType<'a, String>>,
     output_definitions: BTreeMap<String, ObjectType<'a, String>>,
+    interface_definitions: BTreeMap<String, InterfaceType<'a, String>>,
+    unions: BTreeMap<String, Union>,
     enums: BTreeMap<String, Enum>,
-    scalars: BTreeSet<String>
+    scalars: BTreeMap<String, Scalar>,
+    schema_definition: Option<SchemaDefinition<'a, String>>,
+    schema_definition_source: Option<String>,
+    declaration_order: HashMap<String, usize>,
+    type_sources: HashMap<String, Option<String>>,
+    extension_field_sources: HashMap<(String, String), Option<String>>,
+    warnings: Vec<Diagnostic>,
+    references: Vec<TypeReference>,
+    errors: Vec<Diagnostic>,
+    extensions: Vec<(Option<String>, TypeExtension<'a, String>)>
 }
 
 impl<'a> GqlDocumentBuilder<'a> {
@@ -32,24 +88,55 @@ impl<'a> GqlDocumentBuilder<'a> {
         GqlDocumentBuilder {
             input_definitions: BTreeMap::new(),
             output_definitions: BTreeMap::new(),
+            interface_definitions: BTreeMap::new(),
+            unions: BTreeMap::new(),
             enums: BTreeMap::new(),
-            scalars: BTreeSet::new()
+            scalars: BTreeMap::new(),
+            schema_definition: None,
+            schema_definition_source: None,
+            declaration_order: HashMap::new(),
+            type_sources: HashMap::new(),
+            extension_field_sources: HashMap::new(),
+            warnings: Vec::new(),
+            references: Vec::new(),
+            errors: Vec::new(),
+            extensions: Vec::new()
         }
     }
 
     fn add_scalar(&mut self, name: &str) {
-        self.scalars.insert(name.to_string());
+        self.scalars.insert(name.to_string(), Scalar { description: None, specified_by_url: None });
     }
 
-    fn add_document(&mut self, schema: Document<'a, String>) {
+    fn add_document(&mut self, schema: Document<'a, String>, source_name: &Option<String>) {
         for definition in schema.definitions {
             match definition {
                 Definition::TypeDefinition(definition) => {
+                    let name = type_definition_name(&definition);
+                    if let Some(previous_source) = self.type_sources.get(name) {
+                        let message = format!(
+                            "Type `{}` is defined more than once, in {} and {}",
+                            name,
+                            source_label(previous_source),
+                            source_label(source_name)
+                        );
+                        let error = Diagnostic::error(diagnostics::DUPLICATE_DEFINITION, message)
+                            .at(source_name, Some(type_definition_position(&definition)));
+                        self.errors.push(error);
+                        continue;
+                    }
+                    self.add_declaration(name);
+                    self.type_sources.insert(name.to_string(), source_name.clone());
+                    self.warn_type_definition_directives(&definition, source_name);
                     match definition {
                         TypeDefinition::Scalar(definition) => {
-                            self.scalars.insert(definition.name);
+                            let specified_by_url = specified_by_url(&definition.directives);
+                            let scalar = Scalar { description: definition.description, specified_by_url };
+                            self.scalars.insert(definition.name, scalar);
                         }
                         TypeDefinition::Object(definition) => {
+                            let path = format!("interfaces of {}", definition.name);
+                            self.add_references(&definition.implements_interfaces, ReferenceKind::Interface, path, source_name, definition.position);
                             self.output_definitions.insert(definition.name.clone(), definition);
                         }
                         TypeDefinition::InputObject(definition) => {
@@ -57,62 +144,439 @@ impl<'a> GqlDocumentBuilder<'a> {
                         }
                         TypeDefinition::Enum(definition) => {
                             let name = definition.name;
-                            let enum_members: Vec<String> = definition.values.iter().map(|it| it.name.clone()).collect();
-                            let enum_def = Enum { name: name.clone(), values: enum_members };
+                            let enum_members: Vec<EnumValue> = definition.values.into_iter().map(to_enum_value).collect();
+                            let enum_def = Enum { name: name.clone(), description: definition.description, values: enum_members };
                             self.enums.insert(name, enum_def);
                         }
-                        TypeDefinition::Union(_) => (),
-                        TypeDefinition::Interface(_) => (),
+                        TypeDefinition::Union(definition) => {
+                            let path = format!("union {}", definition.name);
+                            self.add_references(&definition.types, ReferenceKind::Object, path, source_name, definition.position);
+                            let name = definition.name;
+                            let union_def = Union { name: name.clone(), description: definition.description, members: definition.types };
+                            self.unions.insert(name, union_def);
+                        }
+                        TypeDefinition::Interface(definition) => {
+                            let path = format!("interfaces of {}", definition.name);
+                            self.add_references(&definition.implements_interfaces, ReferenceKind::Interface, path, source_name, definition.position);
+                            self.interface_definitions.insert(definition.name.clone(), definition);
+                        }
+                    }
+                }
+                Definition::SchemaDefinition(definition) => {
+                    if self.schema_definition.is_some() {
+                        let message = format!(
+                            "Schema definition is defined more than once, in {} and {}",
+                            source_label(&self.schema_definition_source),
+                            source_label(source_name)
+                        );
+                        let error = Diagnostic::error(diagnostics::DUPLICATE_DEFINITION, message)
+                            .at(source_name, Some(definition.position));
+                        self.errors.push(error);
+                        continue;
                     }
+                    self.warn_directives("schema", &definition.directives, source_name);
+                    self.schema_definition_source = source_name.clone();
+                    self.schema_definition = Some(definition);
                 }
-                Definition::SchemaDefinition(_) => (),
-                Definition::TypeExtension(_) => (),
-                Definition::DirectiveDefinition(_) => ()
+                Definition::TypeExtension(extension) => {
+                    self.warn_type_extension_directives(&extension, source_name);
+                    self.extensions.push((source_name.clone(), extension));
+                }
+                Definition::DirectiveDefinition(definition) => {
+                    let message = format!("Directive definition @{} is not represented in generated code", definition.name);
+                    let warning = Diagnostic::warning(diagnostics::SKIPPED_DIRECTIVE_DEFINITION, message)
+                        .at(source_name, Some(definition.position));
+                    self.warnings.push(warning);
+                }
+            }
+        }
+    }
+
+    fn warn_type_definition_directives(&mut self, definition: &TypeDefinition<'_, String>, source_name: &Option<String>) {
+        match definition {
+            TypeDefinition::Scalar(definition) => {
+                self.warn_directives(&definition.name, &definition.directives, source_name);
+            }
+            TypeDefinition::Object(definition) => {
+                self.warn_directives(&definition.name, &definition.directives, source_name);
+                self.warn_field_directives(&definition.name, &definition.fields, source_name);
+            }
+            TypeDefinition::Interface(definition) => {
+                self.warn_directives(&definition.name, &definition.directives, source_name);
+                self.warn_field_directives(&definition.name, &definition.fields, source_name);
+            }
+            TypeDefinition::Union(definition) => {
+                self.warn_directives(&definition.name, &definition.directives, source_name);
+            }
+            TypeDefinition::Enum(definition) => {
+                self.warn_directives(&definition.name, &definition.directives, source_name);
+                self.warn_enum_value_directives(&definition.name, &definition.values, source_name);
+            }
+            TypeDefinition::InputObject(definition) => {
+                self.warn_directives(&definition.name, &definition.directives, source_name);
+                self.warn_input_value_directives(&definition.name, &definition.fields, source_name);
             }
         }
     }
 
-    fn build(self) -> GqlDocument {
+    fn warn_type_extension_directives(&mut self, extension: &TypeExtension<'_, String>, source_name: &Option<String>) {
+        match extension {
+            TypeExtension::Scalar(extension) => {
+                self.warn_directives(&extension.name, &extension.directives, source_name);
+            }
+            TypeExtension::Object(extension) => {
+                self.warn_directives(&extension.name, &extension.directives, source_name);
+                self.warn_field_directives(&extension.name, &extension.fields, source_name);
+            }
+            TypeExtension::Interface(extension) => {
+                self.warn_directives(&extension.name, &extension.directives, source_name);
+                self.warn_field_directives(&extension.name, &extension.fields, source_name);
+            }
+            TypeExtension::Union(extension) => {
+                self.warn_directives(&extension.name, &extension.directives, source_name);
+            }
+            TypeExtension::Enum(extension) => {
+                self.warn_directives(&extension.name, &extension.directives, source_name);
+                self.warn_enum_value_directives(&extension.name, &extension.values, source_name);
+            }
+            TypeExtension::InputObject(extension) => {
+                self.warn_directives(&extension.name, &extension.directives, source_name);
+                self.warn_input_value_directives(&extension.name, &extension.fields, source_name);
+            }
+        }
+    }
+
+    fn warn_field_directives(
+        &mut self,
+        type_name: &str,
+        fields: &[graphql_parser::schema::Field<'_, String>],
+        source_name: &Option<String>
+    ) {
+        for field in fields {
+            let path = format!("{}.{}", type_name, field.name);
+            self.warn_directives(&path, &field.directives, source_name);
+            for argument in &field.arguments {
+                let path = format!("{}({})", path, argument.name);
+                self.warn_directives(&path, &argument.directives, source_name);
+            }
+        }
+    }
+
+    fn warn_input_value_directives(
+        &mut self,
+        type_name: &str,
+        fields: &[graphql_parser::schema::InputValue<'_, String>],
+        source_name: &Option<String>
+    ) {
+        for field in fields {
+            let path = format!("{}.{}", type_name, field.name);
+            self.warn_directives(&path, &field.directives, source_name);
+        }
+    }
+
+    fn warn_enum_value_directives(
+        &mut self,
+        type_name: &str,
+        values: &[graphql_parser::schema::EnumValue<'_, String>],
+        source_name: &Option<String>
+    ) {
+        for value in values {
+            let path = format!("{}.{}", type_name, value.name);
+            self.warn_directives(&path, &value.directives, source_name);
+        }
+    }
+
+    fn warn_directives(&mut self, path: &str, directives: &[Directive<'_, String>], source_name: &Option<String>) {
+        for directive in directives {
+            if !HANDLED_DIRECTIVES.contains(&directive.name.as_str()) {
+                let message = format!("Directive @{} on {} is skipped", directive.name, path);
+                let warning = Diagnostic::warning(diagnostics::SKIPPED_DIRECTIVE, message)
+                    .at(source_name, Some(directive.position));
+                self.warnings.push(warning);
+            }
+        }
+    }
+
+    fn add_references(&mut self, names: &[String], kind: ReferenceKind, path: String, source_name: &Option<String>, position: Pos) {
+        for name in names {
+            self.references.push(TypeReference {
+                name: name.clone(),
+                kind,
+                path: path.clone(),
+                source_name: source_name.clone(),
+                position
+            });
+        }
+    }
+
+    fn add_declaration(&mut self, name: &str) {
+        let index = self.declaration_order.len();
+        self.declaration_order.entry(name.to_string()).or_insert(index);
+    }
+
+    fn declaration_index(&self, name: &str) -> usize {
+        self.declaration_order.get(name).copied().unwrap_or(usize::MAX)
+    }
+
+    fn in_declaration_order<T>(&self, mut items: Vec<T>, name: impl Fn(&T) -> &str) -> Vec<T> {
+        items.sort_by_key(|item| self.declaration_index(name(item)));
+        items
+    }
+
+    fn apply_extensions(&mut self, diagnostics: &mut Vec<Diagnostic>) {
+        let extensions = std::mem::take(&mut self.extensions);
+        for (source_name, extension) in extensions {
+            let undefined_extension = |kind: &str, name: &str, position: Pos| {
+                let message = format!("Unable to extend {} `{}`, no such type is defined", kind, name);
+                Diagnostic::error(diagnostics::UNDEFINED_EXTENSION, message).at(&source_name, Some(position))
+            };
+            match extension {
+                TypeExtension::Scalar(extension) => {
+                    match self.scalars.get_mut(&extension.name) {
+                        Some(scalar) => {
+                            if let Some(url) = specified_by_url(&extension.directives) {
+                                scalar.specified_by_url = Some(url);
+                            }
+                        }
+                        None => diagnostics.push(undefined_extension("scalar", &extension.name, extension.position))
+                    }
+                }
+                TypeExtension::Object(extension) => {
+                    match self.output_definitions.get_mut(&extension.name) {
+                        Some(definition) => {
+                            for field in &extension.fields {
+                                self.extension_field_sources.insert((extension.name.clone(), field.name.clone()), source_name.clone());
+                            }
+                            definition.implements_interfaces.extend(extension.implements_interfaces.clone());
+                            definition.fields.extend(extension.fields);
+                            let path = format!("interfaces of {}", extension.name);
+                            self.add_references(&extension.implements_interfaces, ReferenceKind::Interface, path, &source_name, extension.position);
+                        }
+                        None => diagnostics.push(undefined_extension("type", &extension.name, extension.position))
+                    }
+                }
+                TypeExtension::Interface(extension) => {
+                    match self.interface_definitions.get_mut(&extension.name) {
+                        Some(definition) => {
+                            for field in &extension.fields {
+                                self.extension_field_sources.insert((extension.name.clone(), field.name.clone()), source_name.clone());
+                            }
+                            definition.implements_interfaces.extend(extension.implements_interfaces.clone());
+                            definition.fields.extend(extension.fields);
+                            let path = format!("interfaces of {}", extension.name);
+                            self.add_references(&extension.implements_interfaces, ReferenceKind::Interface, path, &source_name, extension.position);
+                        }
+                        None => diagnostics.push(undefined_extension("interface", &extension.name, extension.position))
+                    }
+                }
+                TypeExtension::Union(extension) => {
+                    match self.unions.get_mut(&extension.name) {
+                        Some(definition) => {
+                            definition.members.extend(extension.types.clone());
+                            let path = format!("union {}", extension.name);
+                            self.add_references(&extension.types, ReferenceKind::Object, path, &source_name, extension.position);
+                        }
+                        None => diagnostics.push(undefined_extension("union", &extension.name, extension.position))
+                    }
+                }
+                TypeExtension::Enum(extension) => {
+                    match self.enums.get_mut(&extension.name) {
+                        Some(definition) => {
+                            definition.values.extend(extension.values.into_iter().map(to_enum_value));
+                        }
+                        None => diagnostics.push(undefined_extension("enum", &extension.name, extension.position))
+                    }
+                }
+                TypeExtension::InputObject(extension) => {
+                    match self.input_definitions.get_mut(&extension.name) {
+                        Some(definition) => {
+                            for field in &extension.fields {
+                                self.extension_field_sources.insert((extension.name.clone(), field.name.clone()), source_name.clone());
+                            }
+                            definition.directives.extend(extension.directives);
+                            definition.fields.extend(extension.fields);
+                        }
+                        None => diagnostics.push(undefined_extension("input", &extension.name, extension.position))
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_references(&self, diagnostics: &mut Vec<Diagnostic>) {
+        for reference in &self.references {
+            let matches = match reference.kind {
+                ReferenceKind::Object => self.output_definitions.contains_key(&reference.name),
+                ReferenceKind::Interface => self.interface_definitions.contains_key(&reference.name)
+            };
+            if !matches {
+                let diagnostic = schema::invalid_reference(&reference.name, &reference.path, reference.kind, self.is_defined(&reference.name))
+                    .at(&reference.source_name, Some(reference.position));
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    fn is_defined(&self, name: &str) -> bool {
+        self.scalars.contains_key(name)
+            || self.enums.contains_key(name)
+            || self.input_definitions.contains_key(name)
+            || self.output_definitions.contains_key(name)
+            || self.interface_definitions.contains_key(name)
+            || self.unions.contains_key(name)
+    }
+
+    fn field_source(&self, type_name: &str, field_name: &str) -> &Option<String> {
+        let key = (type_name.to_string(), field_name.to_string());
+        match self.extension_field_sources.get(&key) {
+            Some(source_name) => source_name,
+            None => self.type_sources.get(type_name).unwrap_or(&None)
+        }
+    }
+
+    fn build(mut self) -> Result<(GqlDocument, Vec<Diagnostic>), Vec<Diagnostic>> {
+        let mut diagnostics: Vec<Diagnostic> = std::mem::take(&mut self.errors);
+        self.apply_extensions(&mut diagnostics);
+        self.check_references(&mut diagnostics);
         let inputs = self.input_definitions
             .values()
-            .map(|object| self.to_input_object(object))
+            .map(|object| self.to_input_object(object, &mut diagnostics))
             .collect();
         let outputs = self.output_definitions
             .values()
-            .map(|object| self.to_output_object(object))
+            .map(|object| self.to_output_object(object, &mut diagnostics))
             .collect();
-        GqlDocument {
+        let interfaces = self.interface_definitions
+            .values()
+            .map(|interface| self.to_interface(interface, &mut diagnostics))
+            .collect();
+        if !diagnostics.is_empty() {
+            let mut problems = std::mem::take(&mut self.warnings);
+            problems.extend(diagnostics);
+            return Err(problems);
+        }
+        let root_types = self.to_root_types();
+        let inputs = self.in_declaration_order(inputs, |object: &Object| &object.name);
+        let outputs = self.in_declaration_order(outputs, |object: &Object| &object.name);
+        let interfaces = self.in_declaration_order(interfaces, |interface: &Interface| &interface.name);
+        let unions = std::mem::take(&mut self.unions).into_values().collect();
+        let unions = self.in_declaration_order(unions, |union: &Union| &union.name);
+        let enums = std::mem::take(&mut self.enums).into_values().collect();
+        let enums = self.in_declaration_order(enums, |enum_def: &Enum| &enum_def.name);
+        let document = GqlDocument {
             inputs,
             outputs,
+            interfaces,
+            unions,
             scalars: self.scalars,
-            enums: self.enums.into_values().collect()
+            enums,
+            root_types
+        };
+        Ok((document, self.warnings))
+    }
+
+    fn to_root_types(&self) -> RootTypes {
+        match &self.schema_definition {
+            Some(definition) => RootTypes {
+                query: definition.query.clone(),
+                mutation: definition.mutation.clone(),
+                subscription: definition.subscription.clone()
+            },
+            None => RootTypes {
+                query: self.default_root_type(schema::DEFAULT_QUERY_TYPE),
+                mutation: self.default_root_type(schema::DEFAULT_MUTATION_TYPE),
+                subscription: self.default_root_type(schema::DEFAULT_SUBSCRIPTION_TYPE)
+            }
         }
     }
 
-    fn to_output_object(&self, definition: &ObjectType<'_, String>) -> Object {
-        let fields: Vec<Field> = definition.fields
+    fn default_root_type(&self, name: &str) -> Option<String> {
+        if self.output_definitions.contains_key(name) {
+            Some(name.to_string())
+        } else {
+            None
+        }
+    }
+
+    fn to_output_object(&self, definition: &ObjectType<'_, String>, diagnostics: &mut Vec<Diagnostic>) -> Object {
+        Object {
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            fields: self.to_output_fields(&definition.name, &definition.fields, diagnostics),
+            one_of: false
+        }
+    }
+
+    fn to_interface(&self, definition: &InterfaceType<'_, String>, diagnostics: &mut Vec<Diagnostic>) -> Interface {
+        let implementors = self.output_definitions
+            .values()
+            .filter(|object| object.implements_interfaces.contains(&definition.name))
+            .map(|object| object.name.clone())
+            .collect();
+        let implementors = self.in_declaration_order(implementors, |name: &String| name);
+        Interface {
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            fields: self.to_output_fields(&definition.name, &definition.fields, diagnostics),
+            interfaces: definition.implements_interfaces.clone(),
+            implementors
+        }
+    }
+
+    fn to_output_fields(
+        &self,
+        type_name: &str,
+        fields: &[graphql_parser::schema::Field<'_, String>],
+        diagnostics: &mut Vec<Diagnostic>
+    ) -> Vec<Field> {
+        fields
             .iter()
             .map(|field| {
                 let field_name = &field.name;
                 let field_type = &field.field_type;
                 let field_arguments = &field.arguments;
+                let description = field.description.clone();
+                let deprecated = deprecation(&field.directives);
+                let source_name = self.field_source(type_name, field_name);
+                let context = TypeContext {
+                    path: format!("{}.{}", type_name, field_name),
+                    source_name,
+                    position: field.position
+                };
                 
                 if field_arguments.is_empty() {
-                    Field { name: field_name.clone(), field_type: self.to_gql_type(field_type, true) }
+                    Field {
+                        name: field_name.clone(),
+                        description,
+                        deprecated,
+                        default_value: None,
+                        field_type: self.to_gql_type(field_type, true, &context, diagnostics)
+                    }
                 } else {
-                    let func_output = self.to_gql_type(field_type, true);
+                    let func_output = self.to_gql_type(field_type, true, &context, diagnostics);
                     let args = field_arguments
                         .iter()
                         .map(|arg| {
+                            let context = TypeContext {
+                                path: format!("{}.{}({})", type_name, field_name, arg.name),
+                                source_name,
+                                position: arg.position
+                            };
                             Argument {
                                 name: arg.name.clone(),
-                                argument_type: self.to_gql_type(&arg.value_type, true),
+                                description: arg.description.clone(),
+                                deprecated: deprecation(&arg.directives),
+                                default_value: arg.default_value.as_ref().map(|value| value.to_string()),
+                                argument_type: self.to_gql_type(&arg.value_type, true, &context, diagnostics),
                                 type_name: arg.value_type.to_string()
                             }
                         })
                         .collect();
                     Field {
                         name: field_name.clone(),
+                        description,
+                        deprecated,
+                        default_value: None,
                         field_type: GqlType::Function {
                             inputs: args,
                             output: Box::new(func_output)
@@ -120,34 +584,49 @@ impl<'a> GqlDocumentBuilder<'a> {
                     }
                 }
             })
-            .collect();
-        Object {
-            name: definition.name.clone(),
-            fields
-        }
+            .collect()
     }
 
-    fn to_input_object(&self, definition: &InputObjectType<'_, String>) -> Object {
+    fn to_input_object(&self, definition: &InputObjectType<'_, String>, diagnostics: &mut Vec<Diagnostic>) -> Object {
         let fields = definition.fields.iter()
             .map(|field| {
                 let name = &field.name;
                 let field_type = &field.value_type;
-                Field { name: name.clone(), field_type: self.to_gql_type(field_type, true) }
+                let context = TypeContext {
+                    path: format!("{}.{}", definition.name, name),
+                    source_name: self.field_source(&definition.name, name),
+                    position: field.position
+                };
+                Field {
+                    name: name.clone(),
+                    description: field.description.clone(),
+                    deprecated: deprecation(&field.directives),
+                    default_value: field.default_value.as_ref().map(|value| value.to_string()),
+                    field_type: self.to_gql_type(field_type, true, &context, diagnostics)
+                }
             })
             .collect();
         Object {
             name: definition.name.clone(),
-            fields
+            description: definition.description.clone(),
+            fields,
+            one_of: definition.directives.iter().any(|directive| directive.name == "oneOf")
         }
     }
 
-    fn to_gql_type(&self, field_type: &Type<'_, String>, is_nullable: bool) -> GqlType {
+    fn to_gql_type(
+        &self,
+        field_type: &Type<'_, String>,
+        is_nullable: bool,
+        context: &TypeContext<'_>,
+        diagnostics: &mut Vec<Diagnostic>
+    ) -> GqlType {
         match field_type {
             Type::NonNullType(inner) => {
-                self.to_gql_type(inner, false)
+                self.to_gql_type(inner, false, context, diagnostics)
             }
             Type::ListType(inner) => {
-                let inner_type = self.to_gql_type(inner, true);
+                let inner_type = self.to_gql_type(inner, true, context, diagnostics);
                 if is_nullable {
                     GqlType::Nullable(Box::new(GqlType::List(Box::new(inner_type))))
                 } else {
@@ -155,14 +634,21 @@ impl<'a> GqlDocumentBuilder<'a> {
                 }
             }
             Type::NamedType(name) => {
-                let inner = if self.scalars.contains(&name.to_string()) {
+                let inner = if self.scalars.contains_key(name) {
                     GqlType::Scalar(name.clone())
                 } else if self.enums.contains_key(name) {
                     GqlType::Enum(name.clone())
-                } else if self.input_definitions.contains_key(name) || self.output_definitions.contains_key(name) {
+                } else if self.input_definitions.contains_key(name)
+                    || self.output_definitions.contains_key(name)
+                    || self.interface_definitions.contains_key(name)
+                    || self.unions.contains_key(name) {
                     GqlType::Object(name.clone())
                 } else {
-                    panic!("Unknown type {}", name);
+                    let message = format!("Unknown type `{}` in {}", name, context.path);
+                    let diagnostic = Diagnostic::error(diagnostics::UNKNOWN_TYPE, message)
+                        .at(context.source_name, Some(context.position));
+                    diagnostics.push(diagnostic);
+                    GqlType::Scalar(name.clone())
                 };
                 if is_nullable {
                     GqlType::Nullable(Box::new(inner))
@@ -173,3 +659,186 @@ impl<'a> GqlDocumentBuilder<'a> {
         }
     }
 }
+
+fn to_enum_value(value: graphql_parser::schema::EnumValue<'_, String>) -> EnumValue {
+    let deprecated = deprecation(&value.directives);
+    EnumValue { name: value.name, description: value.description, deprecated }
+}
+
+fn deprecation(directives: &[Directive<'_, String>]) -> Option<String> {
+    directives
+        .iter()
+        .find(|directive| directive.name == "deprecated")
+        .map(|directive| {
+            let reason = directive.arguments
+                .iter()
+                .find(|(name, _)| name == "reason")
+                .and_then(|(_, value)| match value {
+                    Value::String(reason) => Some(reason.clone()),
+                    _ => None
+                });
+            reason.unwrap_or_else(|| schema::DEFAULT_DEPRECATION_REASON.to_string())
+        })
+}
+
+fn specified_by_url(directives: &[Directive<'_, String>]) -> Option<String> {
+    directives
+        .iter()
+        .find(|directive| directive.name == "specifiedBy")
+        .and_then(|directive| directive.arguments.iter().find(|(name, _)| name == "url"))
+        .and_then(|(_, value)| match value {
+            Value::String(url) => Some(url.clone()),
+            _ => None
+        })
+}
+
+fn type_definition_position(definition: &TypeDefinition<'_, String>) -> Pos {
+    match definition {
+        TypeDefinition::Scalar(definition) => definition.position,
+        TypeDefinition::Object(definition) => definition.position,
+        TypeDefinition::Interface(definition) => definition.position,
+        TypeDefinition::Union(definition) => definition.position,
+        TypeDefinition::Enum(definition) => definition.position,
+        TypeDefinition::InputObject(definition) => definition.position
+    }
+}
+
+fn source_label(source_name: &Option<String>) -> &str {
+    source_name.as_deref().unwrap_or("<stdin>")
+}
+
+fn type_definition_name<'a>(definition: &'a TypeDefinition<'_, String>) -> &'a str {
+    match definition {
+        TypeDefinition::Scalar(definition) => &definition.name,
+        TypeDefinition::Object(definition) => &definition.name,
+        TypeDefinition::Interface(definition) => &definition.name,
+        TypeDefinition::Union(definition) => &definition.name,
+        TypeDefinition::Enum(definition) => &definition.name,
+        TypeDefinition::InputObject(definition) => &definition.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_sdl_string(sdl: &str) -> Result<GqlDocument, Vec<Diagnostic>> {
+        from_sdl_sources(&[SchemaSource { name: None, content: sdl.to_string() }]).map(|(document, _)| document)
+    }
+
+    fn summary(diagnostic: &Diagnostic) -> String {
+        let location = diagnostic.location.as_ref().unwrap();
+        let position = location.position.unwrap();
+        let source_name = source_label(&location.source_name);
+        format!("{} {}:{}:{} {}", diagnostic.code, source_name, position.line, position.column, diagnostic.message)
+    }
+
+    fn summaries(diagnostics: &[Diagnostic]) -> Vec<String> {
+        diagnostics.iter().map(summary).collect()
+    }
+
+    #[test]
+    fn merges_extensions_into_definitions() {
+        let sdl = "
+            extend type Query { b: Int }
+            type Query { a: Int }
+            input Filter { a: Int }
+            extend input Filter { b: Int }
+            enum Role { ADMIN }
+            extend enum Role { GUEST }
+            union Result = Query
+            extend union Result = Page
+            type Page { c: Int }
+            interface Node { id: ID }
+            extend interface Node { name: String }
+            extend type Page implements Node
+        ";
+        let document = from_sdl_string(sdl).ok().unwrap();
+        let field_names = |fields: &[Field]| fields.iter().map(|field| field.name.clone()).collect::<Vec<String>>();
+        let query = document.outputs.iter().find(|object| object.name == "Query").unwrap();
+        assert_eq!(field_names(&query.fields), ["a", "b"]);
+        assert_eq!(field_names(&document.inputs[0].fields), ["a", "b"]);
+        let values: Vec<&str> = document.enums[0].values.iter().map(|value| value.name.as_str()).collect();
+        assert_eq!(values, ["ADMIN", "GUEST"]);
+        assert_eq!(document.unions[0].members, ["Query", "Page"]);
+        assert_eq!(field_names(&document.interfaces[0].fields), ["id", "name"]);
+        assert_eq!(document.interfaces[0].implementors, ["Page"]);
+    }
+
+    #[test]
+    fn rejects_extension_of_undefined_type() {
+        let problems = from_sdl_string("type Query { a: Int }\n\nextend type Missing { b: Int }").err().unwrap();
+        assert_eq!(summaries(&problems), ["E0003 <stdin>:3:8 Unable to extend type `Missing`, no such type is defined"]);
+    }
+
+    fn source(name: &str, content: &str) -> SchemaSource {
+        SchemaSource { name: Some(name.to_string()), content: content.to_string() }
+    }
+
+    #[test]
+    fn merges_types_from_all_sources() {
+        let sources = [
+            source("a.graphql", "type Query { user: User }"),
+            source("b.graphql", "type User { id: ID! }")
+        ];
+        let (document, _) = from_sdl_sources(&sources).ok().unwrap();
+        let names: Vec<&str> = document.outputs.iter().map(|object| object.name.as_str()).collect();
+        assert_eq!(names, ["Query", "User"]);
+    }
+
+    #[test]
+    fn reports_duplicate_definition_with_both_sources() {
+        let sources = [
+            source("a.graphql", "type Query { user: User }\ntype User { id: ID! }"),
+            source("b.graphql", "type User { name: String }")
+        ];
+        let problems = from_sdl_sources(&sources).err().unwrap();
+        assert_eq!(summaries(&problems), ["E0006 b.graphql:1:1 Type `User` is defined more than once, in a.graphql and b.graphql"]);
+    }
+
+    #[test]
+    fn reports_all_unknown_types_with_positions() {
+        let sources = [
+            source("query.graphql", "type Query {\n    user(filter: Filter): User\n    roles: [Role!]!\n}"),
+            source("user.graphql", "type User {\n    id: ID!\n    avatar: Image\n}\n\nextend type User {\n  posts: [Post]\n}")
+        ];
+        let problems = from_sdl_sources(&sources).err().unwrap();
+        assert_eq!(summaries(&problems), [
+            "E0002 query.graphql:2:10 Unknown type `Filter` in Query.user(filter)",
+            "E0002 query.graphql:3:5 Unknown type `Role` in Query.roles",
+            "E0002 user.graphql:3:5 Unknown type `Image` in User.avatar",
+            "E0002 user.graphql:7:3 Unknown type `Post` in User.posts"
+        ]);
+    }
+
+    #[test]
+    fn reports_invalid_union_members_and_implemented_interfaces() {
+        let sdl = "type Query { a: A }\nunion U = Missing | Node\ninterface Node { id: ID }\ntype A implements Node & Ghost { id: ID }\ninterface B implements Role { id: ID }\nenum Role { ADMIN }\nextend union U = Role";
+        let problems = from_sdl_string(sdl).err().unwrap();
+        assert_eq!(summaries(&problems), [
+            "E0002 <stdin>:2:1 Unknown type `Missing` in union U",
+            "E0007 <stdin>:2:1 `Node` in union U is not an object type",
+            "E0002 <stdin>:4:1 Unknown type `Ghost` in interfaces of A",
+            "E0007 <stdin>:5:1 `Role` in interfaces of B is not an interface",
+            "E0007 <stdin>:7:8 `Role` in union U is not an object type"
+        ]);
+    }
+
+    #[test]
+    fn locates_syntax_errors() {
+        let problems = from_sdl_sources(&[source("broken.graphql", "type Query {\n    a: Int\n    b Int\n}")]).err().unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].code, diagnostics::SYNTAX_ERROR);
+        let position = problems[0].location.as_ref().unwrap().position.unwrap();
+        assert_eq!((position.line, position.column), (3, 7));
+    }
+
+    #[test]
+    fn warns_about_schema_directives() {
+        let sources = [source("a.graphql", "schema @link(url: \"https://example.com\") { query: Query }\ntype Query { id: ID }")];
+        let (_, warnings) = from_sdl_sources(&sources).ok().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, diagnostics::SKIPPED_DIRECTIVE);
+        assert_eq!(warnings[0].message, "Directive @link on schema is skipped");
+    }
+}
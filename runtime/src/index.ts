@@ -13,6 +13,10 @@ export function scalar<Encoded, Decoded>(
 	return codec
 }
 
+export const TYPENAME_FIELD = "__typename"
+
+export const FRAGMENTS_FIELD = "__on"
+
 export type Codec = Record<string, CodecField>
 
 export type CodecField = {
@@ -21,7 +25,8 @@ export type CodecField = {
 	args?: Record<string, {
 		type: string,
 		encode: (value: unknown) => unknown
-	}>
+	}>,
+	fragments?: Record<string, () => Codec>
 }
 
 export type Encoder = Record<string, (value: unknown) => unknown>
@@ -32,9 +37,10 @@ export function decodeObjectResponse(value: Object, decoder: Codec): Record<stri
 
 export function decodeObject<Encoded>(value: Encoded, decoder: Codec): Record<string, unknown> {
     if (value != null && typeof value === "object") {
+        const fragmentDecoder = resolveFragmentCodec(value, decoder)
         return Object.entries(value)
             .map(([fieldName, fieldValue]) => {
-                const decodeField = decoder[fieldName]?.decode
+                const decodeField = (fragmentDecoder?.[fieldName] ?? decoder[fieldName])?.decode
                 if (decodeField === undefined) {
                     throw new Error(`Missing decoder for ${fieldName} on ${decoder}`)
                 } else {
@@ -49,6 +55,16 @@ export function decodeObject<Encoded>(value: Encoded, decoder: Codec): Record<st
     }
 }
 
+function resolveFragmentCodec(value: object, decoder: Codec): Codec | undefined {
+    const typeName = (value as Record<string, unknown>)[TYPENAME_FIELD]
+    const fragments = decoder[FRAGMENTS_FIELD]?.fragments
+    if (typeof typeName === "string" && fragments !== undefined) {
+        return fragments[typeName]?.()
+    } else {
+        return undefined
+    }
+}
+
 export function decodeList<Encoded, Decoded>(value: unknown, decode: (_: Encoded) => Decoded): Decoded[] {
     if (Array.isArray(value)) {
         return value.map(decode)
@@ -101,6 +117,15 @@ export function encodeObject(value: unknown, encoder: Encoder): { [name: string]
     }
 }
 
+export function encodeOneOf(value: unknown, encoder: Encoder): { [name: string]: unknown } {
+    const encoded = encodeObject(value, encoder)
+    const fieldNames = Object.keys(encoded)
+    if (fieldNames.length !== 1 || encoded[fieldNames[0]] === null) {
+        throw new Error(`Expected exactly one non-null field for oneOf input, but instead got ${fieldNames.join(", ")}`)
+    }
+    return encoded
+}
+
 export type QScalar<T> = { scalar: T }
 
 export type QObject<T> = { object: T }
@@ -113,6 +138,8 @@ export type QNull<T> = { value: T }
 
 export type QFun<Params, Fields> = { params: Params, fields: Fields }
 
+export type QFragments<T> = { fragments: T }
+
 export type Narrowable = | string | number | bigint | boolean
 
 export type Exact<A, W> =
@@ -137,8 +164,13 @@ export type FieldRequest<TSchema, TInputSchema, TScalars extends Record<string,
 	T extends QList<infer A> ? FieldRequest<TSchema, TInputSchema, TScalars, A> :
 	T extends QNull<infer U> ? FieldRequest<TSchema, TInputSchema, TScalars, U> :
 	T extends QFun<infer Params, infer Fields> ? [InputObjectType<TInputSchema, TScalars, Params>, FieldRequest<TSchema, TInputSchema, TScalars, Fields>] :
+	T extends QFragments<infer Members> ? FragmentRequest<TSchema, TInputSchema, TScalars, Members> :
 	never
 
+export type FragmentRequest<TSchema, TInputSchema, TScalars extends Record<string, Scalar<unknown, unknown>>, Members> = Partial<{
+	[K in keyof Members]: ObjectRequest<TSchema, TInputSchema, TScalars, (Members[K] extends keyof TSchema ? TSchema[Members[K]] : never)>
+}>
+
 export type InputObjectType<TInputSchema, TScalars extends Record<string, Scalar<unknown, unknown>>, T> = {
 	[F in keyof T]: InputFieldType<TInputSchema, TScalars, T[F]>
 }
@@ -152,8 +184,18 @@ export type InputFieldType<TInputSchema, TScalars extends Record<string, Scalar<
 	never
 
 export type OutputObjectType<TSchema, TScalars extends Record<string, Scalar<unknown, unknown>>, TRequest, TModel> = {
-	[F in keyof TRequest]: F extends keyof TModel ? OutputFieldType<TSchema, TScalars, TRequest[F], TModel[F]> : never
-}
+	[F in keyof TRequest as F extends typeof FRAGMENTS_FIELD ? never : F]: F extends keyof TModel ? OutputFieldType<TSchema, TScalars, TRequest[F], TModel[F]> : never
+} & OutputFragmentType<TSchema, TScalars, TRequest, TModel>
+
+export type OutputFragmentType<TSchema, TScalars extends Record<string, Scalar<unknown, unknown>>, TRequest, TModel> =
+	TRequest extends { [FRAGMENTS_FIELD]: infer TFragments } ? (
+		TModel extends { [FRAGMENTS_FIELD]: QFragments<infer Members> } ? {
+			[K in keyof Members]: { [TYPENAME_FIELD]: K } & (
+				K extends keyof TFragments ? OutputObjectType<TSchema, TScalars, TFragments[K], (Members[K] extends keyof TSchema ? TSchema[Members[K]] : never)> : {}
+			)
+		}[keyof Members] : never
+	) :
+	unknown
 
 export type OutputFieldType<TSchema, TScalars extends Record<string, Scalar<unknown, unknown>>, TRequest, TModel> =
 	TRequest extends number ? (
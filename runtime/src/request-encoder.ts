@@ -1,4 +1,4 @@
-import { Codec, CodecField, Encoder } from "."
+import { Codec, CodecField, Encoder, FRAGMENTS_FIELD, TYPENAME_FIELD } from "."
 
 export function encodeRequest(operationType: string, request: Object, codec: Codec): EncodedRequest {
     const encoding = new GraphQLRequest()
@@ -54,17 +54,21 @@ class GraphQLRequest {
 
     public encodeObject(obj: unknown, codec: Codec): string {
         if (obj !== null && typeof obj === "object") {
-            const request = Object.entries(obj)
+            const fields = Object.entries(obj)
                 .map(([fieldName, fieldValue]) => {
                     const encoderField = codec[fieldName]
                     if (encoderField === undefined) {
                         throw new Error(`Encoder has no field for ${fieldName} on ${codec}`)
+                    } else if (fieldName === FRAGMENTS_FIELD) {
+                        return this.encodeFragments(fieldValue, encoderField)
                     } else {
                         return this.encodeField(fieldName, fieldValue, encoderField)
                     }
                 })
-                .join(" ")
-            return `{ ${request} }`
+            if (FRAGMENTS_FIELD in obj && !(TYPENAME_FIELD in obj)) {
+                fields.push(TYPENAME_FIELD)
+            }
+            return `{ ${fields.join(" ")} }`
         } else {
             throw new Error(`Expected object, but instead got ${obj}`)
         }
@@ -84,6 +88,26 @@ class GraphQLRequest {
         }
     }
     
+    private encodeFragments(value: unknown, codec: CodecField): string {
+        if (codec.fragments === undefined) {
+            throw new Error(`Missing fragments prop on ${codec}`)
+        } else if (value !== null && typeof value === "object") {
+            const fragments = codec.fragments
+            return Object.entries(value)
+                .map(([typeName, fragmentRequest]) => {
+                    const fragmentCodec = fragments[typeName]
+                    if (fragmentCodec === undefined) {
+                        throw new Error(`No fragment found for type ${typeName}`)
+                    } else {
+                        return `... on ${typeName} ${this.encodeObject(fragmentRequest, fragmentCodec())}`
+                    }
+                })
+                .join(" ")
+        } else {
+            throw new Error(`Expected object, but instead got ${value}`)
+        }
+    }
+
     private encodeFunction(field: string, params: Object, fieldRequest: unknown, encoder: CodecField): string {
         if (encoder.args === undefined) {
             throw new Error(`Missing args prop on ${encoder}`)